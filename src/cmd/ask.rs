@@ -0,0 +1,312 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::command::parse_argument_value;
+use crate::constraints::Constraint;
+use crate::constraints::choice::{resolve_choices, ChoiceConstraint};
+use crate::domain::{Argument, ArgumentValue, Choice, Constraints};
+use crate::prompt::with_color;
+
+/// Asks the value of the missing argument on the terminal. Returns `None` if the input is cancelled.
+/// The flag is never asked, since it is just off when it is missing.
+pub fn ask_argument(arg: &Argument) -> Option<ArgumentValue> {
+    match &arg.constraint {
+        Constraints::Choice(options) => {
+            let choices = match resolve_choices(&options.source) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("{}", with_color(1, e));
+                    return None;
+                }
+            };
+
+            // The chosen one is parsed with the choices already computed, so that they are not computed again.
+            let index = select(&arg.name, &choices)?;
+            ChoiceConstraint::from_options(options, choices.clone()).parse_value(Some(&choices[index].key)).ok()
+        },
+        _ => ask_text(arg)
+    }
+}
+
+/// Asks the yes-or-no question until it is answered.
+pub fn confirm(question: &str) -> Option<bool> {
+    loop {
+        match read_line(&format!("{} [y/n]: ", question), true)?.to_lowercase().as_str() {
+            "y" | "yes" => return Some(true),
+            "n" | "no" => return Some(false),
+            _ => continue
+        }
+    }
+}
+
+/// Asks the value until the constraint of the argument accepts it. The value of the secret argument is not echoed.
+fn ask_text(arg: &Argument) -> Option<ArgumentValue> {
+    loop {
+        let input = read_line(&format!("{}: ", arg.name), !arg.secret)?;
+
+        match parse_argument_value(arg, &input) {
+            Ok(v) => return Some(v),
+            Err(e) => eprintln!("{}", with_color(1, e))
+        }
+    }
+}
+
+/// Reads the line after the prompt. If the line should not be echoed but the terminal can't hide it, nothing is asked.
+fn read_line(prompt: &str, echo: bool) -> Option<String> {
+    let mode = match echo {
+        true => None,
+        false => match TerminalMode::raw() {
+            Some(m) => Some(m),
+            None => {
+                eprintln!("{}", with_color(1, "The input can't be hidden on this terminal, so the secret value is not asked."));
+                return None;
+            }
+        }
+    };
+
+    eprint!("{}", prompt);
+    io::stderr().flush().ok()?;
+
+    // The hidden line is read key by key in the raw mode, where Ctrl-C is read as the key to cancel.
+    if let Some(mode) = mode {
+        let line = read_hidden_line();
+        drop(mode);
+        eprintln!();
+
+        return line;
+    }
+
+    let mut line = String::new();
+    let read = io::stdin().lock().read_line(&mut line).ok()?;
+    if read == 0 {
+        return None;
+    }
+
+    Some(line.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+fn read_hidden_line() -> Option<String> {
+    let mut line = Vec::new();
+    let mut buffer = [0u8; 1];
+    loop {
+        match io::stdin().lock().read(&mut buffer) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {}
+        }
+
+        match edit_line(&mut line, buffer[0]) {
+            LineAction::Continue => continue,
+            LineAction::Done => return Some(String::from_utf8_lossy(&line).into_owned()),
+            LineAction::Cancel => return None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum LineAction {
+    Continue,
+    Done,
+    Cancel,
+}
+
+/// Edits the line with the key read in the raw mode. Backspace removes the last character, and Ctrl-U the whole line.
+fn edit_line(line: &mut Vec<u8>, key: u8) -> LineAction {
+    match key {
+        b'\r' | b'\n' => return LineAction::Done,
+        b'\x03' => return LineAction::Cancel,
+        b'\x04' if line.is_empty() => return LineAction::Cancel,
+        b'\x7f' | b'\x08' => {
+            // The continuation bytes of UTF-8 are removed together with the leading one.
+            while let Some(b) = line.pop() {
+                if b & 0xc0 != 0x80 {
+                    break;
+                }
+            }
+        },
+        b'\x15' => line.clear(),
+        k if k.is_ascii_control() => {},
+        k => line.push(k)
+    }
+
+    LineAction::Continue
+}
+
+/// Lets the user choose one of the choices with the arrow keys, or by the number if the terminal can't be raw.
+fn select(name: &str, choices: &[Choice]) -> Option<usize> {
+    if choices.is_empty() {
+        eprintln!("{}", with_color(1, format!("There is no choice for {}.", name)));
+        return None;
+    }
+
+    let _mode = match TerminalMode::raw() {
+        Some(m) => m,
+        None => return select_by_number(name, choices)
+    };
+
+    eprintln!("{}: (Use the arrow keys, and Enter to choose)", name);
+    render_choices(choices, 0, false);
+
+    let mut selected = 0;
+    let mut buffer = [0u8; 8];
+    loop {
+        let read = match io::stdin().lock().read(&mut buffer) {
+            Ok(0) | Err(_) => return None,
+            Ok(r) => r
+        };
+
+        match key_action(&buffer[..read], selected, choices.len()) {
+            KeyAction::Move(index) => {
+                selected = index;
+                render_choices(choices, selected, true);
+            },
+            KeyAction::Select => return Some(selected),
+            KeyAction::Cancel => return None,
+            KeyAction::Ignore => continue
+        }
+    }
+}
+
+fn select_by_number(name: &str, choices: &[Choice]) -> Option<usize> {
+    eprintln!("{}:", name);
+    for (i, choice) in choices.iter().enumerate() {
+        eprintln!("  {}) {}", i + 1, render_choice(choice));
+    }
+
+    loop {
+        let input = read_line("Choose the number: ", true)?;
+        match input.parse::<usize>() {
+            Ok(n) if (1..=choices.len()).contains(&n) => return Some(n - 1),
+            _ => continue
+        }
+    }
+}
+
+fn render_choices(choices: &[Choice], selected: usize, redraw: bool) {
+    if redraw {
+        eprint!("\x1b[{}A", choices.len());
+    }
+
+    for (i, choice) in choices.iter().enumerate() {
+        let line = if i == selected {
+            with_color(6, format!("> {}", render_choice(choice)))
+        } else {
+            format!("  {}", render_choice(choice))
+        };
+        eprintln!("\r\x1b[2K{}", line);
+    }
+}
+
+fn render_choice(choice: &Choice) -> String {
+    match &choice.help {
+        Some(help) => format!("{}  ({})", choice.key, help),
+        None => choice.key.clone()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum KeyAction {
+    Move(usize),
+    Select,
+    Cancel,
+    Ignore,
+}
+
+/// Decides what the key pressed during the selection does. The cursor wraps around at the both ends.
+fn key_action(key: &[u8], selected: usize, len: usize) -> KeyAction {
+    match key {
+        b"\x1b[A" | b"\x1bOA" | b"k" => KeyAction::Move(selected.checked_sub(1).unwrap_or(len - 1)),
+        b"\x1b[B" | b"\x1bOB" | b"j" => KeyAction::Move((selected + 1) % len),
+        b"\r" | b"\n" => KeyAction::Select,
+        b"\x03" | b"\x04" | b"\x1b" | b"q" => KeyAction::Cancel,
+        _ => KeyAction::Ignore
+    }
+}
+
+/// Changes the mode of the terminal until this is dropped.
+#[cfg(target_family = "unix")]
+struct TerminalMode(libc::termios);
+
+#[cfg(target_family = "unix")]
+impl TerminalMode {
+    /// Reads every key without waiting for Enter. Ctrl-C is also read as the key, so that the mode is always restored.
+    fn raw() -> Option<Self> {
+        Self::change(libc::ICANON | libc::ECHO | libc::ISIG)
+    }
+
+    fn change(disabled: libc::tcflag_t) -> Option<Self> {
+        // SAFETY: The attributes are written to the zeroed struct by tcgetattr before they are used.
+        unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return None;
+            }
+
+            let mut changed = original;
+            changed.c_lflag &= !disabled;
+            changed.c_cc[libc::VMIN] = 1;
+            changed.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &changed) != 0 {
+                return None;
+            }
+
+            Some(Self(original))
+        }
+    }
+}
+
+#[cfg(target_family = "unix")]
+impl Drop for TerminalMode {
+    fn drop(&mut self) {
+        // SAFETY: The attributes are the ones read by tcgetattr.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
+
+#[cfg(target_family = "windows")]
+struct TerminalMode;
+
+#[cfg(target_family = "windows")]
+impl TerminalMode {
+    fn raw() -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{edit_line, key_action, KeyAction, LineAction};
+
+    #[rstest(key, selected, expected,
+        case(b"\x1b[B", 0, KeyAction::Move(1)),
+        case(b"j", 2, KeyAction::Move(0)),
+        case(b"\x1b[A", 1, KeyAction::Move(0)),
+        case(b"k", 0, KeyAction::Move(2)),
+        case(b"\r", 1, KeyAction::Select),
+        case(b"\x03", 1, KeyAction::Cancel),
+        case(b"x", 1, KeyAction::Ignore),
+    )]
+    fn handles_key_during_selection(key: &[u8], selected: usize, expected: KeyAction) {
+        assert_eq!(key_action(key, selected, 3), expected)
+    }
+
+    #[rstest(line, key, expected, expected_line,
+        case("ab", b'c', LineAction::Continue, "abc"),
+        case("ab", b'\r', LineAction::Done, "ab"),
+        case("ab", b'\x03', LineAction::Cancel, "ab"),
+        case("", b'\x04', LineAction::Cancel, ""),
+        case("ab", b'\x04', LineAction::Continue, "ab"),
+        case("aé", b'\x7f', LineAction::Continue, "a"),
+        case("", b'\x7f', LineAction::Continue, ""),
+        case("ab", b'\x15', LineAction::Continue, ""),
+        case("ab", b'\x1b', LineAction::Continue, "ab"),
+    )]
+    fn edits_hidden_line(line: &str, key: u8, expected: LineAction, expected_line: &str) {
+        let mut line = line.as_bytes().to_vec();
+
+        assert_eq!(edit_line(&mut line, key), expected);
+        assert_eq!(String::from_utf8(line).unwrap(), expected_line);
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+
+use crate::constraints::choice::resolve_choices;
+use crate::domain::{Argument, Config, Constraints, PathKind, PathOptions};
+
+/// Lists the candidates for the last word of the line, like `rxe --complete deploy --env pr`.
+pub fn complete(config: &Config, line: &[String]) -> Vec<String> {
+    let (current, previous) = match line.split_last() {
+        Some((current, previous)) => (current.as_str(), previous),
+        None => ("", line)
+    };
+
+    let cmd = match previous.first() {
+        Some(name) => match config.get_command(name) {
+            Some(c) => c,
+            None => return Vec::new()
+        },
+        None => return sorted(config.command.iter().map(|c| c.name.clone()), current)
+    };
+
+    // The value joined with `=`, like `--env=pr`.
+    if let Some((name, value)) = current.strip_prefix("--").and_then(|c| c.split_once('=')) {
+        return cmd.get_argument(name)
+            .map(|a| complete_value(a, value).into_iter().map(|v| format!("--{}={}", name, v)).collect())
+            .unwrap_or_default();
+    }
+
+    let taking_value = previous.last()
+        .and_then(|p| cmd.get_argument_by_option(p))
+        .filter(|a| !matches!(a.constraint, Constraints::Flag(_)));
+    match taking_value {
+        Some(arg) => complete_value(arg, current),
+        None if current.starts_with('-') => sorted(cmd.args.iter().map(|a| format!("--{}", a.name)), current),
+        None => Vec::new()
+    }
+}
+
+fn complete_value(arg: &Argument, current: &str) -> Vec<String> {
+    let candidates = match &arg.constraint {
+        Constraints::Choice(options) => resolve_choices(&options.source)
+            .map(|choices| choices.into_iter().map(|c| c.key).collect())
+            .unwrap_or_default(),
+        Constraints::Path(options) => list_entries(options, current),
+        _ => Vec::new()
+    };
+
+    sorted(candidates.into_iter(), current)
+}
+
+/// Lists the entries in the directory of the typed path. The directories end with `/` so that they can be followed.
+fn list_entries(options: &PathOptions, current: &str) -> Vec<String> {
+    let (dir, typed_name) = match current.rfind('/') {
+        Some(i) => (&current[..=i], &current[i + 1..]),
+        None => ("", current)
+    };
+
+    let entries = match fs::read_dir(if dir.is_empty() { "." } else { dir }) {
+        Ok(e) => e,
+        Err(_) => return Vec::new()
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            // The hidden entries are listed only if they are being typed.
+            if name.starts_with('.') && !typed_name.starts_with('.') {
+                return None;
+            }
+
+            if entry.path().is_dir() {
+                return Some(format!("{}{}/", dir, name));
+            }
+
+            let extension = Path::new(&name).extension().and_then(|e| e.to_str()).unwrap_or_default();
+            let acceptable = options.kind != Some(PathKind::Dir)
+                && (options.extensions.is_empty() || options.extensions.iter().any(|e| e == extension));
+
+            acceptable.then(|| format!("{}{}", dir, name))
+        })
+        .collect()
+}
+
+fn sorted(candidates: impl Iterator<Item = String>, current: &str) -> Vec<String> {
+    let mut candidates = candidates.filter(|c| c.starts_with(current)).collect::<Vec<_>>();
+    candidates.sort();
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::config::DeserializedConfig;
+    use crate::domain::Config;
+
+    use super::complete;
+
+    fn config() -> Config {
+        let yaml = r#"
+cmd:
+  deploy:
+    args:
+      env:
+        choice: [prod, stg]
+        short: e
+      manifest:
+        path:
+          extensions: [toml]
+      dry:
+        flag:
+    run: echo {env}
+  destroy:
+    run: echo
+"#;
+        serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap()
+    }
+
+    #[rstest(input, expected,
+        case(vec![], vec!["deploy", "destroy"]),
+        case(vec!["dep"], vec!["deploy"]),
+        case(vec!["deploy", "--"], vec!["--dry", "--env", "--manifest"]),
+        case(vec!["deploy", "--env", ""], vec!["prod", "stg"]),
+        case(vec!["deploy", "-e", "p"], vec!["prod"]),
+        case(vec!["deploy", "--env=s"], vec!["--env=stg"]),
+        case(vec!["deploy", "--dry", ""], vec![]),
+        case(vec!["deploy", "--manifest", "Cargo"], vec!["Cargo.toml"]),
+        case(vec!["deploy", "--manifest", "sr"], vec!["src/"]),
+        case(vec!["unknown", "--"], vec![]),
+    )]
+    fn completes_last_word(input: Vec<&str>, expected: Vec<&str>) {
+        let input: Vec<String> = input.iter().map(ToString::to_string).collect();
+
+        assert_eq!(complete(&config(), &input), expected);
+    }
+}
@@ -1,20 +1,59 @@
+use std::collections::HashMap;
+
+use atty::Stream::Stdin;
+
 use crate::cmd::Environment;
-use crate::command::parse;
+use crate::cmd::ask::{ask_argument, confirm};
+use crate::cmd::complete::complete;
+use crate::cmd::help::{is_help_requested, render_help};
+use crate::cmd::options::{parse_options, Options};
+use crate::command::{parse, parse_asking};
+use crate::condition::evaluate;
 use crate::config::{read_from_yaml, ReadError};
-use crate::domain::{Config, InputtedCommand};
-use crate::placeholder::fill_placeholder;
+use crate::dependency::{resolve, Task};
+use crate::domain::{ArgumentValue, Command, Config, InputtedCommand, Step};
+use crate::placeholder::{fill_placeholder, fill_placeholder_hiding, fill_placeholder_masking, PlaceholderParseError};
 use crate::prompt::error;
-use crate::runner::{run_script, ExecuteStatus};
+use crate::runner::{env_reference, run_steps, ExecuteStatus, Script, StepFailure};
+use crate::runner::schedule::{run_jobs, Job, JobResult};
+use crate::runner::signal::trap;
 
 const DEFAULT_FILES: [&str; 4] = ["rxe.yaml", "rxe.yml", ".rxe.yaml", ".rxe.yml"];
 
 pub fn execute(env: Environment) -> Result<i32, i32> {
+    let (options, line) = parse_rxe_options(&env).map_err(|_| 1)?;
     let config = read_config(&env).map_err(|_| 1)?;
-    let args = parse_command_arg(&config, &env).map_err(|_| 1)?;
-    let script = generate_script(&config, &args).map_err(|_| 1)?;
-    let result = run(&script).map_err(|_| 1)?;
+    if options.complete {
+        complete(&config, line).iter().for_each(|c| println!("{}", c));
+        return Ok(0);
+    }
+    if is_help_requested(&config, line) {
+        return show_help(&config, line);
+    }
 
-    Ok(result)
+    let args = parse_command_arg(&config, line, &options).map_err(|_| 1)?;
+    let tasks = resolve_dependencies(&config, args).map_err(|_| 1)?;
+
+    confirm_tasks(&config, &tasks, &options).map_err(|_| 1)?;
+
+    let (last, dependencies) = tasks.split_last().expect("The command itself should be in the tasks");
+    trap();
+    run_dependencies(&config, dependencies, &options)?;
+
+    let job = prepare_job(&config, last).map_err(|_| 1)?;
+    run(&job)
+}
+
+fn parse_rxe_options(env: &Environment) -> Result<(Options, &[String]), ()> {
+    match parse_options(&env.args[1..]) {
+        Ok(o) => Ok(o),
+        Err(e) => {
+            error(format!("Could not parse the option: {}", e));
+            error("Please check the argument you passed to `rxe`.");
+
+            Err(())
+        }
+    }
 }
 
 fn read_config(env: &Environment) -> Result<Config, ()> {
@@ -41,8 +80,26 @@ fn read_config(env: &Environment) -> Result<Config, ()> {
     Err(())
 }
 
-fn parse_command_arg(config: &Config, env: &Environment) -> Result<InputtedCommand, ()> {
-    let cmd = parse(config, &env.args[1..]);
+fn show_help(config: &Config, line: &[String]) -> Result<i32, i32> {
+    match config.get_command(&line[0]) {
+        Some(c) => {
+            println!("{}", render_help(c));
+            Ok(0)
+        },
+        None => {
+            error(format!("Could not show the help: The command '{}' does not exist.", line[0]));
+            Err(1)
+        }
+    }
+}
+
+fn parse_command_arg(config: &Config, line: &[String], options: &Options) -> Result<InputtedCommand, ()> {
+    // The missing arguments are asked only if someone is likely to be on the terminal.
+    let cmd = if !options.no_input && atty::is(Stdin) {
+        parse_asking(config, line, &mut ask_argument)
+    } else {
+        parse(config, line)
+    };
     match cmd {
         Ok(c) => Ok(c),
         Err(e) => {
@@ -54,12 +111,11 @@ fn parse_command_arg(config: &Config, env: &Environment) -> Result<InputtedComma
     }
 }
 
-fn generate_script(config: &Config, cmd: &InputtedCommand) -> Result<String, ()> {
-    let script = fill_placeholder(&config.get_command(&cmd.name).unwrap().run, &cmd.args);
-    match script {
-        Ok(s) => Ok(s),
+fn resolve_dependencies(config: &Config, cmd: InputtedCommand) -> Result<Vec<Task>, ()> {
+    match resolve(config, cmd) {
+        Ok(t) => Ok(t),
         Err(e) => {
-            error(format!("Could not fill the placeholder of the command: {}", e));
+            error(format!("Could not resolve the dependencies of the command: {}", e));
             error("Please check the configuration file.");
 
             Err(())
@@ -67,18 +123,153 @@ fn generate_script(config: &Config, cmd: &InputtedCommand) -> Result<String, ()>
     }
 }
 
-fn run(script: &str) -> Result<i32, ()> {
-    match run_script(script) {
-        Ok(ExecuteStatus::Exited(c)) => Ok(c),
-        Ok(ExecuteStatus::Terminated) => {
-            error("The program was terminated by the signal!");
+/// Asks the confirmation of every command which requires it before anything is run.
+/// Without the terminal, such commands are refused unless `--yes` is given.
+fn confirm_tasks(config: &Config, tasks: &[Task], options: &Options) -> Result<(), ()> {
+    for task in tasks {
+        let definition = config.get_command(&task.command.name).unwrap();
+        let question = match &definition.confirm {
+            Some(q) => fill_placeholder_masking(q, &task.command.args, &secret_names(definition)).map_err(|e| {
+                error(format!("Could not fill the placeholder of the confirmation of '{}': {}", definition.name, e));
+                error("Please check the configuration file.");
+            })?,
+            None => continue
+        };
 
-            Err(())
+        if options.yes {
+            continue;
         }
-        Err(e) => {
-            error(format!("An error occurred during the execution: {}", e));
+        if options.no_input || !atty::is(Stdin) {
+            error(format!("The command '{}' requires the confirmation: {}", definition.name, question));
+            error("Run it on the terminal, or pass `--yes` to `rxe` to confirm it beforehand.");
+            return Err(());
+        }
+        if confirm(&question) != Some(true) {
+            error(format!("The command '{}' was not confirmed.", definition.name));
+            return Err(());
+        }
+    }
 
-            Err(())
+    Ok(())
+}
+
+fn run_dependencies(config: &Config, tasks: &[Task], options: &Options) -> Result<(), i32> {
+    let jobs = tasks.iter()
+        .map(|t| prepare_job(config, t))
+        .collect::<Result<Vec<_>, ()>>()
+        .map_err(|_| 1)?;
+
+    let results = run_jobs(&jobs, options.jobs, options.keep_going);
+
+    let mut exit_code = None;
+    for (job, result) in jobs.iter().zip(&results) {
+        if let JobResult::Done(Err(f)) = result {
+            let code = report_failure(&format!("the dependency '{}'", job.name), f);
+            exit_code.get_or_insert(code);
+        }
+    }
+
+    match exit_code {
+        Some(c) => Err(c),
+        None => Ok(())
+    }
+}
+
+fn prepare_job(config: &Config, task: &Task) -> Result<Job, ()> {
+    let cmd = &task.command;
+    let definition = config.get_command(&cmd.name).unwrap();
+
+    let secrets = secret_names(definition);
+
+    let steps = [
+        generate_steps("before ", &config.before, &cmd.args, &secrets)?,
+        generate_steps("", &definition.run, &cmd.args, &secrets)?,
+        generate_steps("after ", &config.after, &cmd.args, &secrets)?,
+    ].concat();
+
+    Ok(Job {
+        name: cmd.name.clone(),
+        steps,
+        finally: generate_steps("finally ", &definition.finally, &cmd.args, &secrets)?,
+        needs: task.needs.clone()
+    })
+}
+
+fn secret_names(definition: &Command) -> Vec<String> {
+    definition.args.iter()
+        .filter(|a| a.secret)
+        .map(|a| a.name.clone())
+        .collect()
+}
+
+fn generate_steps(label: &str, steps: &[Step], values: &HashMap<String, ArgumentValue>, secrets: &[String]) -> Result<Vec<Script>, ()> {
+    let mut scripts = Vec::new();
+    for (i, step) in steps.iter().enumerate() {
+        let name = step.name.clone().unwrap_or_else(|| format!("{}#{}", label, i + 1));
+
+        let enabled = match &step.condition {
+            Some(c) => evaluate(c, values).map_err(|e| {
+                error(format!("Could not evaluate the condition of the step '{}': {}", name, e));
+                error("Please check the configuration file.");
+            })?,
+            None => true
+        };
+        if !enabled {
+            continue;
+        }
+
+        let script = fill_step(&name, step, values, secrets).map_err(|e| {
+            error(format!("Could not fill the placeholder of the step '{}': {}", name, e));
+            error("Please check the configuration file.");
+        })?;
+        scripts.push(script);
+    }
+
+    Ok(scripts)
+}
+
+fn fill_step(name: &str, step: &Step, values: &HashMap<String, ArgumentValue>, secrets: &[String]) -> Result<Script, PlaceholderParseError> {
+    // The secret values are passed through the environment variables, not to be written into the script file.
+    let (body, secret_env) = fill_placeholder_hiding(&step.run, values, secrets, |v, q| env_reference(step.shell.as_deref(), v, q))?;
+
+    let mut env = step.env.iter()
+        .map(|(k, v)| Ok((k.clone(), fill_placeholder(v, values)?)))
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    env.extend(secret_env);
+
+    Ok(Script {
+        name: name.to_string(),
+        body,
+        shell: step.shell.clone(),
+        cwd: step.cwd.as_deref().map(|c| fill_placeholder(c, values)).transpose()?,
+        env,
+        continue_on_error: step.continue_on_error
+    })
+}
+
+fn run(job: &Job) -> Result<i32, i32> {
+    let is_single_script = job.steps.len() == 1 && job.finally.is_empty();
+
+    match run_steps(&job.steps, &job.finally, None) {
+        Ok(()) => Ok(0),
+        Err(StepFailure { cause: Ok(ExecuteStatus::Exited(c)), .. }) if is_single_script => Ok(c),
+        Err(f) => Err(report_failure(&format!("the command '{}'", job.name), &f))
+    }
+}
+
+fn report_failure(subject: &str, failure: &StepFailure) -> i32 {
+    match &failure.cause {
+        Ok(ExecuteStatus::Exited(c)) => {
+            error(format!("The step '{}' of {} exited with the status {}.", failure.step, subject, c));
+            *c
+        },
+        Ok(status @ ExecuteStatus::Terminated(s)) => {
+            error(format!("The step '{}' of {} was terminated by the signal {}!", failure.step, subject, s));
+            status.code()
+        },
+        Err(e) => {
+            error(format!("An error occurred during the execution of the step '{}' of {}: {}", failure.step, subject, e));
+            1
         }
     }
 }
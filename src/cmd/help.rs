@@ -0,0 +1,197 @@
+use crate::constraints::choice::resolve_choices;
+use crate::domain::{Argument, Command, Config, Constraints};
+
+/// Returns whether the help of the command is requested, like `rxe <cmd> --help`.
+/// `--help` and `-h` are not regarded as the help if the command has the argument for them, or if they are the value of the argument.
+pub fn is_help_requested(config: &Config, line: &[String]) -> bool {
+    let cmd = line.first().and_then(|c| config.get_command(c));
+    let is_taken = |option: &str| cmd.is_some_and(|c| c.get_argument_by_option(option).is_some());
+
+    let mut words = line.iter().skip(1);
+    while let Some(word) = words.next() {
+        if (word == "--help" || word == "-h") && !is_taken(word) {
+            return true;
+        }
+
+        // The word following the argument like `--user` is its value.
+        let takes_value = cmd
+            .and_then(|c| c.get_argument_by_option(word))
+            .is_some_and(|a| !matches!(a.constraint, Constraints::Flag(_)));
+        if takes_value {
+            words.next();
+        }
+    }
+
+    false
+}
+
+pub fn render_help(cmd: &Command) -> String {
+    let mut lines = vec![format!("Usage: rxe {} [arguments]", cmd.name)];
+
+    let mut args = cmd.args.iter().collect::<Vec<_>>();
+    args.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if !args.is_empty() {
+        lines.push("".to_string());
+        lines.push("Arguments:".to_string());
+    }
+    for arg in args {
+        let secret = if arg.secret { " (secret)" } else { "" };
+        let env = arg.env.as_ref().map(|e| format!(" [env: {}]", e)).unwrap_or_default();
+        lines.push(format!("  {:<24}{}{}{}", render_names(arg), render_type(&arg.constraint), secret, env));
+
+        if let Constraints::Choice(options) = &arg.constraint {
+            let choices = match resolve_choices(&options.source) {
+                Ok(c) => c,
+                Err(e) => {
+                    lines.push(format!("      ({})", e));
+                    continue;
+                }
+            };
+
+            let width = choices.iter().map(|c| c.key.len()).max().unwrap_or_default();
+            for choice in &choices {
+                let mut line = format!("      {:<width$}", choice.key, width = width);
+                if let Some(value) = choice.value.as_ref().filter(|v| **v != choice.key) {
+                    line += &format!("  => {}", value);
+                }
+                if let Some(help) = &choice.help {
+                    line += &format!("  ({})", help);
+                }
+                lines.push(line.trim_end().to_string());
+            }
+        }
+    }
+
+    if !cmd.presets.is_empty() {
+        lines.push("".to_string());
+        lines.push("Presets:".to_string());
+    }
+    for preset in &cmd.presets {
+        // The values of the secret arguments are masked as they are anywhere else.
+        let words = preset.args.iter()
+            .map(|(name, value)| match cmd.get_argument(name) {
+                Some(a) if a.secret => format!("--{}=****", name),
+                _ => format!("--{}={}", name, value)
+            })
+            .collect::<Vec<_>>();
+        lines.push(format!("  {:<24}{}", format!("@{}", preset.name), words.join(" ")));
+    }
+
+    lines.join("\n")
+}
+
+fn render_names(arg: &Argument) -> String {
+    match &arg.short_hand {
+        Some(s) => format!("--{}, -{}", arg.name, s),
+        None => format!("--{}", arg.name)
+    }
+}
+
+fn render_type(constraint: &Constraints) -> &str {
+    match constraint {
+        Constraints::Text(_) => "text",
+        Constraints::Flag(_) => "flag",
+        Constraints::Number(_) => "number",
+        Constraints::Choice(_) => "choice",
+        Constraints::Path(_) => "path",
+        Constraints::Map(_) => "map",
+        Constraints::Duration => "duration",
+        Constraints::Size => "size",
+        Constraints::Date => "date",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::config::DeserializedConfig;
+    use crate::domain::{Argument, ArgumentRelations, Choice, ChoiceSource, Command, Config, Constraints, Preset, TextOptions};
+
+    use super::{is_help_requested, render_help};
+
+    #[rstest(input, expected,
+        case(vec!["deploy", "--help"], true),
+        case(vec!["deploy", "--env", "prod", "-h"], true),
+        case(vec!["deploy", "--env", "prod"], false),
+        case(vec!["deploy", "--env", "-h"], false),
+        case(vec!["deploy", "--env", "prod", "--dry", "-h"], true),
+        case(vec!["ssh", "-h", "example.com"], false),
+        case(vec!["ssh", "--help"], true),
+        case(vec!["unknown", "-h"], true),
+        case(vec!["--help"], false),
+    )]
+    fn detects_help_request(input: Vec<&str>, expected: bool) {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+            cmd:
+              deploy: { args: { env: { text: }, dry: { flag: } } }
+              ssh: { args: { host: { text:, short: h } } }
+        "#).unwrap().try_into().unwrap();
+        let input: Vec<String> = input.iter().map(ToString::to_string).collect();
+
+        assert_eq!(is_help_requested(&config, &input), expected);
+    }
+
+    #[test]
+    fn renders_arguments_with_choices() {
+        let cmd = Command {
+            name: "deploy".to_string(),
+            args: vec![
+                Argument {
+                    name: "tag".to_string(),
+                    short_hand: None,
+                    constraint: Constraints::Text(TextOptions::default()),
+                    multi: false,
+                    secret: false,
+                    env: None,
+                    relations: ArgumentRelations::default()
+                },
+                Argument {
+                    name: "env".to_string(),
+                    short_hand: Some("e".to_string()),
+                    constraint: Constraints::Choice(ChoiceSource::Static(vec![
+                        Choice { key: "prod".to_string(), value: Some("production".to_string()), help: Some("Live cluster".to_string()) },
+                        "dev".to_string().into(),
+                    ]).into()),
+                    multi: false,
+                    secret: false,
+                    env: Some("DEPLOY_ENV".to_string()),
+                    relations: ArgumentRelations::default()
+                },
+                Argument {
+                    name: "token".to_string(),
+                    short_hand: None,
+                    constraint: Constraints::Text(TextOptions::default()),
+                    multi: false,
+                    secret: true,
+                    env: None,
+                    relations: ArgumentRelations::default()
+                },
+            ],
+            needs: vec![],
+            run: vec![],
+            finally: vec![],
+            groups: vec![],
+            presets: vec![
+                Preset { name: "prod".to_string(), args: vec![("env".to_string(), "prod".to_string()), ("tag".to_string(), "v1".to_string()), ("token".to_string(), "s3cr3t".to_string())] },
+            ],
+            confirm: None
+        };
+
+        let expected = [
+            "Usage: rxe deploy [arguments]",
+            "",
+            "Arguments:",
+            "  --env, -e               choice [env: DEPLOY_ENV]",
+            "      prod  => production  (Live cluster)",
+            "      dev",
+            "  --tag                   text",
+            "  --token                 text (secret)",
+            "",
+            "Presets:",
+            "  @prod                   --env=prod --tag=v1 --token=****",
+        ].join("\n");
+        assert_eq!(render_help(&cmd), expected);
+    }
+}
@@ -1,4 +1,8 @@
+pub(crate) mod ask;
+pub(crate) mod complete;
 pub(crate) mod exec;
+pub(crate) mod help;
+pub(crate) mod options;
 
 pub struct Environment {
     pub config_file: Option<String>,
@@ -0,0 +1,113 @@
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Options {
+    pub jobs: usize,
+    pub keep_going: bool,
+    pub no_input: bool,
+    pub yes: bool,
+    /// Lists the candidates for the last word of the rest of the line, instead of running the command.
+    pub complete: bool,
+}
+impl Default for Options {
+    fn default() -> Self {
+        Self { jobs: 1, keep_going: false, no_input: false, yes: false, complete: false }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OptionError {
+    UnknownOption(String),
+    MalformedJobs(String),
+}
+impl Display for OptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionError::UnknownOption(o) => write!(f, "'{}' is not the option of rxe.", o),
+            OptionError::MalformedJobs(j) => write!(f, "'{}' is not available as the number of jobs. Specify the number larger than 0.", j),
+        }
+    }
+}
+
+pub fn parse_options(line: &[String]) -> Result<(Options, &[String]), OptionError> {
+    let mut options = Options::default();
+
+    let mut index = 0;
+    while let Some(current) = line.get(index) {
+        if !current.starts_with('-') {
+            break;
+        }
+        index += 1;
+
+        match current.as_str() {
+            "-j" | "--jobs" => {
+                let value = line.get(index).ok_or_else(|| OptionError::MalformedJobs("".to_string()))?;
+                index += 1;
+
+                options.jobs = parse_jobs(value)?;
+            },
+            "-k" | "--keep-going" => options.keep_going = true,
+            "--fail-fast" => options.keep_going = false,
+            "--no-input" => options.no_input = true,
+            "-y" | "--yes" => options.yes = true,
+            // The rest of the line is the one being completed, even if it looks like the options of rxe.
+            "--complete" => {
+                options.complete = true;
+                break;
+            },
+            _ => {
+                let jobs = current.strip_prefix("--jobs=").or_else(|| current.strip_prefix("-j"));
+                match jobs {
+                    Some(j) => options.jobs = parse_jobs(j)?,
+                    None => return Err(OptionError::UnknownOption(current.to_string()))
+                }
+            }
+        }
+    }
+
+    Ok((options, &line[index..]))
+}
+
+fn parse_jobs(value: &str) -> Result<usize, OptionError> {
+    match value.parse::<usize>() {
+        Ok(j) if j > 0 => Ok(j),
+        _ => Err(OptionError::MalformedJobs(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{parse_options, Options, OptionError};
+
+    #[rstest(input, expected, rest,
+        case(vec!["test", "-j", "2"], Options::default(), vec!["test", "-j", "2"]),
+        case(vec!["-j", "4", "test"], Options { jobs: 4, ..Options::default() }, vec!["test"]),
+        case(vec!["-j4", "test"], Options { jobs: 4, ..Options::default() }, vec!["test"]),
+        case(vec!["--jobs=3", "-k", "test"], Options { jobs: 3, keep_going: true, ..Options::default() }, vec!["test"]),
+        case(vec!["--keep-going", "--fail-fast"], Options::default(), vec![]),
+        case(vec!["--no-input", "test"], Options { no_input: true, ..Options::default() }, vec!["test"]),
+        case(vec!["-y", "--no-input", "test"], Options { no_input: true, yes: true, ..Options::default() }, vec!["test"]),
+        case(vec!["--complete", "-j", ""], Options { complete: true, ..Options::default() }, vec!["-j", ""]),
+    )]
+    fn accepts_leading_options(input: Vec<&str>, expected: Options, rest: Vec<&str>) {
+        let input: Vec<String> = input.iter().map(ToString::to_string).collect();
+
+        let (options, line) = parse_options(&input).expect("Should success, but failed");
+        assert_eq!(options, expected);
+        assert_eq!(line, rest);
+    }
+
+    #[rstest(input, expected,
+        case(vec!["-j", "0", "test"], OptionError::MalformedJobs("0".to_string())),
+        case(vec!["-j"], OptionError::MalformedJobs("".to_string())),
+        case(vec!["--jobs=many"], OptionError::MalformedJobs("many".to_string())),
+        case(vec!["--unknown", "test"], OptionError::UnknownOption("--unknown".to_string())),
+    )]
+    fn declines_incorrect_options(input: Vec<&str>, expected: OptionError) {
+        let input: Vec<String> = input.iter().map(ToString::to_string).collect();
+
+        assert_eq!(parse_options(&input).unwrap_err(), expected);
+    }
+}
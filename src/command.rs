@@ -1,10 +1,14 @@
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::fs;
 
-use crate::constraints::{ValueParseError, Constraint};
-use crate::domain::{Config, InputtedCommand, Argument, ArgumentValue, Command, Constraints};
-use crate::constraints::{text::TextConstraint, number::NumberConstraint, choice::ChoiceConstraint, flag::FlagConstraint};
+use crate::constraints::{ValueParseError, Constraint, SpecificParseError};
+use crate::constraints::choice::ChoiceCache;
+use crate::helper::identify::IdBox;
+use crate::domain::{Config, InputtedCommand, Argument, ArgumentValue, Command, Constraints, FlagOptions};
+use crate::constraints::{text::TextConstraint, number::NumberConstraint, choice::ChoiceConstraint, flag::FlagConstraint, path::PathConstraint, map::MapConstraint, duration::DurationConstraint, size::SizeConstraint, date::DateConstraint};
 
+use crate::condition::equals;
 use crate::helper::replace_iter::ReplaceIter;
 
 #[derive(Debug, PartialEq)]
@@ -21,6 +25,13 @@ pub enum ParseError {
     InsufficientArgument,
     MalformedLine,
     MalformedArgument(ValueParseError),
+    /// The error of the secret argument. Only its name is kept, since the error of the value may tell the value.
+    MalformedSecretArgument(String),
+    ConflictingArguments { argument: String, conflicting: String },
+    MissingRequiredArgument { argument: String, required_by: String },
+    MissingConditionalArgument { argument: String, condition: String, value: String },
+    ExclusiveArguments { group: String, first: String, second: String },
+    UnreadableArgumentFile { path: String, reason: String },
 }
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -30,21 +41,55 @@ impl Display for ParseError {
             ParseError::ArgumentNotExist => "The argument that does not exist was specified.".to_string(),
             ParseError::InsufficientArgument => "Required argument(s) is missing.".to_string(),
             ParseError::MalformedLine => "The arguments is something wrong. (Perhaps you forgot to specify the value of the argument)".to_string(),
-            ParseError::MalformedArgument(e) => format!("The value of the argument is invalid: {}", e)
+            ParseError::MalformedArgument(e) => format!("The value of the argument is invalid: {}", e),
+            ParseError::MalformedSecretArgument(name) => format!("The value of the secret argument '--{}' is invalid. The detail is not shown not to reveal it.", name),
+            ParseError::ConflictingArguments { argument, conflicting } => {
+                format!("'--{}' cannot be used together with '--{}'.", argument, conflicting)
+            },
+            ParseError::MissingRequiredArgument { argument, required_by } => {
+                format!("'--{}' is required when '--{}' is given.", argument, required_by)
+            },
+            ParseError::MissingConditionalArgument { argument, condition, value } => {
+                format!("'--{}' is required when '--{}' is '{}'.", argument, condition, value)
+            },
+            ParseError::UnreadableArgumentFile { path, reason } => {
+                format!("'@{}' is neither the preset nor the readable file: {}", path, reason)
+            },
+            ParseError::ExclusiveArguments { group, first, second } => {
+                format!("'--{}' and '--{}' cannot be used together, since only one of the group '{}' can be given.", first, second, group)
+            },
         };
 
         write!(f, "{}", msg)
     }
 }
 
+/// Asks the value of the missing argument. `None` means that no value is given.
+pub type Ask<'a> = dyn FnMut(&Argument) -> Option<ArgumentValue> + 'a;
+
 pub fn parse(config: & Config, line: & [impl ToString]) -> Result<InputtedCommand, ParseError> {
-    CommandParser::new(config, line)?.parse()
+    CommandParser::new(config, line)?.parse(None)
+}
+
+/// Parses the line like `parse`, but asks the value of the missing required argument through `ask`.
+/// The argument is still regarded as missing if `ask` returns `None`.
+pub fn parse_asking(
+    config: &Config,
+    line: &[impl ToString],
+    ask: &mut Ask
+) -> Result<InputtedCommand, ParseError> {
+    CommandParser::new(config, line)?.parse(Some(ask))
+}
+
+/// Parses the value of the argument, as if it were specified in the line.
+pub fn parse_argument_value(arg: &Argument, value: &str) -> Result<ArgumentValue, ParseError> {
+    delegate_parse(&arg.constraint, Some(value), &ChoiceCache::default()).map_err(|e| malformed_argument(arg, Some(value), e))
 }
 
-#[derive(Debug)]
 struct CommandParser<'a> {
     cmd: &'a Command,
     arg: Vec<String>,
+    choices: ChoiceCache,
 }
 impl<'a> CommandParser<'a> {
     fn new(config: &'a Config, line: &[impl ToString]) -> Result<Self, ParseError> {
@@ -53,45 +98,71 @@ impl<'a> CommandParser<'a> {
         let cmd = line.next().ok_or(ParseError::NoCommandSpecified)?;
         let cmd = config.get_command(&cmd).ok_or(ParseError::CommandNotExist)?;
 
-        let arg = line.collect();
+        let arg = expand_at_words(cmd, line)?.into_iter().flat_map(|a| split_short_flags(cmd, a)).collect();
 
-        Ok(Self { cmd, arg })
+        Ok(Self { cmd, arg, choices: ChoiceCache::default() })
     }
 
-    fn parse(self) -> Result<InputtedCommand, ParseError> {
+    fn parse(self, ask: Option<&mut Ask>) -> Result<InputtedCommand, ParseError> {
         let mut args_status = self.cmd.args.iter()
             .map(|x| (x.name.clone(), ParseStatus::NotParsed))
             .collect::<HashMap<String, ParseStatus>>();
 
         let mut arg_iter = self.arg.iter().peekable();
         while let Some(current) = arg_iter.next() {
-            let next = arg_iter.peek();
-
             let current_opt = self.parse_option(current)?.ok_or(ParseError::MalformedLine)?;
-            let next_opt = next.map(|n| self.parse_option(*n)).transpose()?.flatten();
 
-            let status = args_status.get_mut(&current_opt.name).ok_or(ParseError::ArgumentNotExist)?;
+            let status = args_status.get_mut(&current_opt.arg.name).ok_or(ParseError::ArgumentNotExist)?;
+
+            // The flag does not take the following word as its value; it is given like `--flag=false`.
+            let value = match current_opt.value {
+                Some(v) => Some(v),
+                None if matches!(current_opt.arg.constraint, Constraints::Flag(_)) => None,
+                None => match arg_iter.peek() {
+                    Some(next) if is_negative_number(&current_opt.arg.constraint, next) || self.parse_option(*next)?.is_none() => {
+                        arg_iter.next().cloned()
+                    },
+                    _ => None
+                }
+            };
 
-            let new_status = match next_opt {
-                Some(_) => {
-                    let parsed = self.delegate_parse(&current_opt.constraint, None)
-                        .map_err(ParseError::MalformedArgument)?;
+            let parsed = delegate_parse(&current_opt.arg.constraint, value.as_deref(), &self.choices)
+                .map_err(|e| malformed_argument(current_opt.arg, value.as_deref(), e))?;
 
-                    ParseStatus::Parsed(parsed)
+            // The counted flag adds up unless the count is given like `--verbose=0`,
+            // and the map collects the pairs every time it is specified.
+            let parsed = match (&status, parsed) {
+                (ParseStatus::Parsed(ArgumentValue::Number(previous)), ArgumentValue::Number(n)) if is_counted(current_opt.arg) && value.is_none() => {
+                    ArgumentValue::Number(previous + n)
                 },
-                None => {
-                    let parsed = self.delegate_parse(&current_opt.constraint, next.map(|s| s.as_str()))
-                        .map_err(ParseError::MalformedArgument)?;
+                (ParseStatus::Parsed(ArgumentValue::Map(previous)), ArgumentValue::Map(pairs)) => {
+                    ArgumentValue::Map(previous.clone().into_iter().chain(pairs).collect())
+                },
+                (_, parsed) => parsed
+            };
 
-                    arg_iter.next();
+            *status = ParseStatus::Parsed(parsed);
+        }
 
-                    ParseStatus::Parsed(parsed)
+        // The missing arguments are asked in the order of the definition.
+        if let Some(ask) = ask {
+            for arg in &self.cmd.args {
+                let status = args_status.get_mut(&arg.name).expect("Every argument should have the status");
+                let is_missing = *status == ParseStatus::NotParsed && arg.env_value().is_none();
+                if !is_missing || delegate_fallback(&arg.constraint) != Err(ValueParseError::ValueRequired) {
+                    continue;
                 }
-            };
 
-            *status = new_status;
+                let value = ask(arg).ok_or(ParseError::InsufficientArgument)?;
+                *status = ParseStatus::Parsed(value);
+            }
         }
 
+        let specified = self.cmd.args.iter()
+            .filter(|a| matches!(args_status.get(&a.name), Some(ParseStatus::Parsed(_))) || a.env_value().is_some())
+            .map(|a| a.name.clone())
+            .collect::<Vec<_>>();
+
         let args = args_status.into_iter()
             .map(|(k, v)| self.unwrap_parse_status(&k, v).map(|v| (k, v)))
             .replace(
@@ -100,42 +171,71 @@ impl<'a> CommandParser<'a> {
             )
             .collect::<Result<HashMap<_, _>, _>>()?;
 
+        let given = self.cmd.args.iter()
+            .filter(|a| specified.contains(&a.name) && !is_turned_off(a, &args[&a.name]))
+            .map(|a| a.name.clone())
+            .collect::<Vec<_>>();
+
+        self.check_relations(&given, &args)?;
+
         Ok(InputtedCommand {
             name: self.cmd.name.to_owned(),
             args
         })
     }
 
-    fn unwrap_parse_status(&self, arg_name: &str, status: ParseStatus) -> Result<ArgumentValue, ParseError> {
-        let arg = self.cmd.get_argument(arg_name)
-            .unwrap_or_else(|| panic!("WTF: '{}' existed in the status, but not in the command", &arg_name));
+    /// Checks the relations among the arguments, after all the values are collected.
+    /// `given` is the arguments given in the line (or asked) and not turned off, in the order of the definition.
+    fn check_relations(&self, given: &[String], values: &HashMap<String, ArgumentValue>) -> Result<(), ParseError> {
+        let is_given = |name: &String| given.contains(name);
+
+        for arg in &self.cmd.args {
+            let relations = &arg.relations;
 
-        match status {
-            ParseStatus::Parsed(v) => Ok(v),
-            _ => self.delegate_fallback(&arg.constraint).map_err(ParseError::MalformedArgument)
+            if is_given(&arg.name) {
+                if let Some(conflicting) = relations.conflicts_with.iter().find(|c| is_given(c)) {
+                    return Err(ParseError::ConflictingArguments { argument: arg.name.clone(), conflicting: conflicting.clone() });
+                }
+                if let Some(required) = relations.requires.iter().find(|r| !is_given(r)) {
+                    return Err(ParseError::MissingRequiredArgument { argument: required.clone(), required_by: arg.name.clone() });
+                }
+            } else {
+                let condition = relations.required_if.iter()
+                    .find(|(name, value)| values.get(name).is_some_and(|v| equals(v, value)));
+                if let Some((condition, value)) = condition {
+                    return Err(ParseError::MissingConditionalArgument { argument: arg.name.clone(), condition: condition.clone(), value: value.clone() });
+                }
+            }
         }
-    }
 
-    fn delegate_parse(&self, constraint: &Constraints, value: Option<&str>) -> Result<ArgumentValue, ValueParseError> {
-        match constraint {
-            Constraints::Text => TextConstraint.parse_value(value),
-            Constraints::Flag => FlagConstraint.parse_value(value),
-            Constraints::Number => NumberConstraint.parse_value(value),
-            Constraints::Choice(c) => ChoiceConstraint::new(c.to_vec()).parse_value(value)
+        for group in &self.cmd.groups {
+            if let [first, second, ..] = &group.args.iter().filter(|a| is_given(a)).collect::<Vec<_>>()[..] {
+                return Err(ParseError::ExclusiveArguments { group: group.name.clone(), first: first.to_string(), second: second.to_string() });
+            }
         }
+
+        Ok(())
     }
 
-    fn delegate_fallback(&self, constraint: &Constraints) -> Result<ArgumentValue, ValueParseError> {
-        match constraint {
-            Constraints::Text => TextConstraint.fallback(),
-            Constraints::Flag => FlagConstraint.fallback(),
-            Constraints::Number => NumberConstraint.fallback(),
-            Constraints::Choice(c) => ChoiceConstraint::new(c.to_vec()).fallback()
+    fn unwrap_parse_status(&self, arg_name: &str, status: ParseStatus) -> Result<ArgumentValue, ParseError> {
+        let arg = self.cmd.get_argument(arg_name)
+            .unwrap_or_else(|| panic!("WTF: '{}' existed in the status, but not in the command", &arg_name));
+
+        // The value from the environment variable is validated in the same way as the one in the line.
+        match (status, arg.env_value()) {
+            (ParseStatus::Parsed(v), _) => Ok(v),
+            (_, Some(value)) => delegate_parse(&arg.constraint, Some(&value), &self.choices).map_err(|e| malformed_argument(arg, Some(&value), e)),
+            _ => delegate_fallback(&arg.constraint).map_err(ParseError::MalformedArgument)
         }
     }
 
-    fn parse_option(&self, str: impl ToString) -> Result<Option<&Argument>, ParseError> {
+    fn parse_option(&self, str: impl ToString) -> Result<Option<OptionToken<'a>>, ParseError> {
         let str = str.to_string();
+        let (str, value) = match str.split_once('=') {
+            Some((name, value)) if name.starts_with('-') => (name, Some(value.to_string())),
+            _ => (str.as_str(), None)
+        };
+
         let arg_name = str.trim_start_matches('-');
         let trimed_dashes = str.len() - arg_name.len();
 
@@ -150,16 +250,155 @@ impl<'a> CommandParser<'a> {
         }
 
         if trimed_dashes == 0 {
-            Ok(None)
-        } else {
-            self.cmd.get_argument(arg_name)
-                .map(Some)
-                .ok_or(ParseError::ArgumentNotExist)
+            return Ok(None);
         }
 
+        let arg = match trimed_dashes {
+            1 => self.cmd.get_argument_by_short_hand(arg_name).or_else(|| self.cmd.get_argument(arg_name)),
+            _ => self.cmd.get_argument(arg_name)
+        };
+        if let Some(arg) = arg {
+            return Ok(Some(OptionToken { arg, value }));
+        }
+
+        // `--no-<flag>` turns the flag off.
+        let negated = arg_name.strip_prefix("no-")
+            .and_then(|n| self.cmd.get_argument(n))
+            .filter(|a| matches!(a.constraint, Constraints::Flag(_)));
+
+        match negated {
+            Some(_) if value.is_some() => Err(ParseError::MalformedArgument(ValueParseError::ValueUneccesary)),
+            Some(arg) => Ok(Some(OptionToken { arg, value: Some("false".to_string()) })),
+            None => Err(ParseError::ArgumentNotExist)
+        }
+    }
+}
+
+fn delegate_parse(constraint: &Constraints, value: Option<&str>, choices: &ChoiceCache) -> Result<ArgumentValue, ValueParseError> {
+    match constraint {
+        Constraints::Text(o) => TextConstraint::new(o.clone()).parse_value(value),
+        Constraints::Flag(o) => FlagConstraint::new(o.clone()).parse_value(value),
+        Constraints::Number(o) => NumberConstraint::new(o.clone()).parse_value(value),
+        Constraints::Choice(o) => choices.constraint(o).map_err(into_parse_failure)?.parse_value(value),
+        Constraints::Path(o) => PathConstraint::new(o.clone()).parse_value(value),
+        Constraints::Map(o) => MapConstraint::new(o.clone()).parse_value(value),
+        Constraints::Duration => DurationConstraint.parse_value(value),
+        Constraints::Size => SizeConstraint.parse_value(value),
+        Constraints::Date => DateConstraint.parse_value(value)
+    }
+}
+
+fn delegate_fallback(constraint: &Constraints) -> Result<ArgumentValue, ValueParseError> {
+    match constraint {
+        Constraints::Text(o) => TextConstraint::new(o.clone()).fallback(),
+        Constraints::Flag(o) => FlagConstraint::new(o.clone()).fallback(),
+        Constraints::Number(o) => NumberConstraint::new(o.clone()).fallback(),
+        // The fallback does not depend on the choices, so they are not computed here.
+        Constraints::Choice(o) => ChoiceConstraint::from_options(o, Vec::new()).fallback(),
+        Constraints::Path(o) => PathConstraint::new(o.clone()).fallback(),
+        Constraints::Map(o) => MapConstraint::new(o.clone()).fallback(),
+        Constraints::Duration => DurationConstraint.fallback(),
+        Constraints::Size => SizeConstraint.fallback(),
+        Constraints::Date => DateConstraint.fallback()
+    }
+}
+
+fn malformed_argument(arg: &Argument, value: Option<&str>, error: ValueParseError) -> ParseError {
+    match value {
+        Some(_) if arg.secret => ParseError::MalformedSecretArgument(arg.name.clone()),
+        _ => ParseError::MalformedArgument(error)
     }
 }
 
+/// Returns whether the word like `-5` is the value of the number argument, rather than the option.
+/// The other values beginning with `-` can be given like `--name=-value`.
+fn is_negative_number(constraint: &Constraints, word: &str) -> bool {
+    matches!(constraint, Constraints::Number(_)) && word.starts_with('-') && word.parse::<f64>().is_ok()
+}
+
+fn is_counted(arg: &Argument) -> bool {
+    matches!(arg.constraint, Constraints::Flag(FlagOptions { count: true, .. }))
+}
+
+/// Returns whether the flag is off, like `--no-dry` or `--verbose=0`, which is not counted as given to the relations.
+fn is_turned_off(arg: &Argument, value: &ArgumentValue) -> bool {
+    match value {
+        ArgumentValue::Flag(on) => !on,
+        ArgumentValue::Number(count) if is_counted(arg) => *count == 0f64,
+        _ => false
+    }
+}
+
+/// Expands `@name` into the arguments of the preset, or of the file if no preset has that name.
+/// The word given as the value of the option, like `--user @alice`, is not expanded.
+fn expand_at_words(cmd: &Command, words: impl Iterator<Item = String>) -> Result<Vec<String>, ParseError> {
+    let mut expanded = Vec::new();
+    let mut is_value = false;
+    for word in words {
+        let is_option_value = is_value;
+        is_value = cmd.get_argument_by_option(&word)
+            .is_some_and(|a| !matches!(a.constraint, Constraints::Flag(_)));
+
+        match word.strip_prefix('@').filter(|n| !n.is_empty() && !is_option_value) {
+            Some(name) => match cmd.get_preset(name) {
+                Some(preset) => expanded.extend(preset.to_words()),
+                None => expanded.extend(read_argument_file(name)?)
+            },
+            None => expanded.push(word)
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Reads the arguments from the file. Each line is split by the whitespaces, except the line like `--name=some value`.
+/// The empty lines and the lines beginning with `#` are ignored.
+fn read_argument_file(path: &str) -> Result<Vec<String>, ParseError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ParseError::UnreadableArgumentFile { path: path.to_string(), reason: e.to_string() })?;
+
+    let words = content.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .flat_map(|l| if l.starts_with("--") && l.contains('=') {
+            vec![l.to_string()]
+        } else {
+            l.split_whitespace().map(ToString::to_string).collect()
+        })
+        .collect();
+
+    Ok(words)
+}
+
+/// Splits the bundled short hands of the flags like `-vvv` into `-v -v -v`.
+fn split_short_flags(cmd: &Command, word: String) -> Vec<String> {
+    let short_hands = match word.strip_prefix('-') {
+        Some(s) if s.chars().count() > 1 && !s.starts_with('-') && !s.contains('=') => s,
+        _ => return vec![word]
+    };
+
+    let is_flag = |c: char| matches!(
+        cmd.get_argument_by_short_hand(&c.to_string()).map(|a| &a.constraint),
+        Some(Constraints::Flag(_))
+    );
+    if !short_hands.chars().all(is_flag) {
+        return vec![word];
+    }
+
+    short_hands.chars().map(|c| format!("-{}", c)).collect()
+}
+
+/// The option in the line, like `--name`, `--name=value` or `--no-name`.
+#[derive(Debug)]
+struct OptionToken<'a> {
+    arg: &'a Argument,
+    value: Option<String>,
+}
+
+fn into_parse_failure(error: impl SpecificParseError + 'static) -> ValueParseError {
+    ValueParseError::ParseFailed(IdBox::new(Box::new(error)))
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -168,34 +407,55 @@ mod tests {
 
     use crate::{domain::{Config, ArgumentValue}, config::DeserializedConfig};
     use crate::constraints::{ValueParseError};
+    use crate::constraints::number::NumberParseError;
+    use crate::helper::identify::IdBox;
 
-    use super::{parse, ParseError};
+    use super::{parse, parse_asking, ParseError};
 
     #[rstest(input, expected,
         case(
             vec!["test", "--type", "core", "--snapshot"],
             crate::map!(<String, ArgumentValue>;
-                "type".to_owned() => ArgumentValue::Text("core".to_owned()),
+                "type".to_owned() => ArgumentValue::Choice { key: "core".to_owned(), value: "core".to_owned() },
                 "snapshot".to_owned() => ArgumentValue::Flag(true),
+                "cache".to_owned() => ArgumentValue::Flag(true),
             )
         ),
         case(
             vec!["test", "--snapshot", "--type", "core"],
             crate::map!(<String, ArgumentValue>;
-                "type".to_owned() => ArgumentValue::Text("core".to_owned()),
+                "type".to_owned() => ArgumentValue::Choice { key: "core".to_owned(), value: "core".to_owned() },
                 "snapshot".to_owned() => ArgumentValue::Flag(true),
+                "cache".to_owned() => ArgumentValue::Flag(true),
             )
         ),
         case(
             vec!["test", "--type", "core"],
             crate::map!(<String, ArgumentValue>;
-                "type".to_owned() => ArgumentValue::Text("core".to_owned()),
+                "type".to_owned() => ArgumentValue::Choice { key: "core".to_owned(), value: "core".to_owned() },
+                "snapshot".to_owned() => ArgumentValue::Flag(false),
+                "cache".to_owned() => ArgumentValue::Flag(true),
+            )
+        ),
+        case(
+            vec!["test", "--type=core", "--snapshot=yes", "--no-cache"],
+            crate::map!(<String, ArgumentValue>;
+                "type".to_owned() => ArgumentValue::Choice { key: "core".to_owned(), value: "core".to_owned() },
+                "snapshot".to_owned() => ArgumentValue::Flag(true),
+                "cache".to_owned() => ArgumentValue::Flag(false),
+            )
+        ),
+        case(
+            vec!["test", "--cache=false", "--snapshot=0", "--type", "core"],
+            crate::map!(<String, ArgumentValue>;
+                "type".to_owned() => ArgumentValue::Choice { key: "core".to_owned(), value: "core".to_owned() },
                 "snapshot".to_owned() => ArgumentValue::Flag(false),
+                "cache".to_owned() => ArgumentValue::Flag(false),
             )
         ),
     )]
     fn accept_correct_input(input: Vec<&str>, expected: HashMap<String, ArgumentValue>) {
-        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().into();
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().try_into().unwrap();
 
         let result = parse(&config, &input);
         assert_eq!(result.unwrap().args, expected)
@@ -214,11 +474,327 @@ mod tests {
             vec!["test", "--type", "--snapshot"],
             ParseError::MalformedArgument(ValueParseError::ValueRequired)
         ),
+        case(
+            vec!["test", "--type", "core", "--snapshot", "true"],
+            ParseError::MalformedLine
+        ),
+        case(
+            vec!["test", "--type", "core", "--no-cache=true"],
+            ParseError::MalformedArgument(ValueParseError::ValueUneccesary)
+        ),
+        case(
+            vec!["test", "--no-type", "core"],
+            ParseError::ArgumentNotExist
+        ),
     )]
     fn decline_incorrect_input(input: Vec<&str>, expected: ParseError) {
-        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().into();
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().try_into().unwrap();
 
         let result = parse(&config, &input);
         assert_eq!(result.unwrap_err(), expected)
     }
+
+    #[rstest(input, expected,
+        case(vec!["log"], 0f64),
+        case(vec!["log", "-v"], 1f64),
+        case(vec!["log", "-vvv"], 3f64),
+        case(vec!["log", "-vv", "--verbose", "-d"], 3f64),
+        case(vec!["log", "--verbose=5"], 5f64),
+        case(vec!["log", "-v", "--verbose=0"], 0f64),
+        case(vec!["log", "-vvv", "--verbose=2", "-v"], 3f64),
+        case(vec!["log", "-vv", "--no-verbose"], 0f64),
+    )]
+    fn counts_flag_occurrences(input: Vec<&str>, expected: f64) {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+cmd:
+  log:
+    args:
+      verbose:
+        flag:
+          count: true
+        short: v
+      dry:
+        flag:
+        short: d
+    run: echo {verbose|repeat=-v}
+"#).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &input).unwrap();
+        assert_eq!(result.args.get("verbose"), Some(&ArgumentValue::Number(expected)))
+    }
+
+    #[test]
+    fn collects_map_pairs() {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+cmd:
+  build:
+    args:
+      set:
+        map:
+    run: docker build {set|each=--build-arg %k=%v} .
+"#).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &["build", "--set", "a=1", "--set=b=2", "--set", "a=3"]).unwrap();
+
+        let expected = std::collections::BTreeMap::from([
+            ("a".to_string(), "3".to_string()),
+            ("b".to_string(), "2".to_string()),
+        ]);
+        assert_eq!(result.args.get("set"), Some(&ArgumentValue::Map(expected)))
+    }
+
+    #[rstest(input, argument, leaked,
+        case(vec!["login", "--token", "hunter2"], "token", vec!["hunter2"]),
+        case(vec!["login", "--token", "long enough", "--pin", "01234"], "pin", vec!["1234"]),
+        case(vec!["login", "--token", "long enough", "--pin", "1e4"], "pin", vec!["1e4", "10000"]),
+    )]
+    fn hides_secret_value_in_error(input: Vec<&str>, argument: &str, leaked: Vec<&str>) {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+cmd:
+  login:
+    args:
+      token:
+        text:
+          min_len: 10
+        secret: true
+      pin:
+        number:
+          max: 999
+        secret: true
+    run: login {token} {pin}
+"#).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &input);
+
+        let error = result.unwrap_err();
+        assert_eq!(error, ParseError::MalformedSecretArgument(argument.to_string()));
+        assert!(leaked.iter().all(|l| !error.to_string().contains(l)));
+    }
+
+    #[rstest(answer, expected,
+        case(Some("frontend"), Ok("frontend")),
+        case(None, Err(ParseError::InsufficientArgument)),
+    )]
+    fn asks_missing_argument(answer: Option<&str>, expected: Result<&str, ParseError>) {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().try_into().unwrap();
+
+        let mut asked = Vec::new();
+        let result = parse_asking(&config, &["test", "--snapshot"], &mut |arg| {
+            asked.push(arg.name.clone());
+            answer.map(|a| ArgumentValue::Choice { key: a.to_string(), value: a.to_string() })
+        });
+
+        assert_eq!(asked, vec!["type".to_string()]);
+        assert_eq!(
+            result.map(|c| c.args.get("type").cloned()),
+            expected.map(|e| Some(ArgumentValue::Choice { key: e.to_string(), value: e.to_string() }))
+        );
+    }
+
+    #[test]
+    fn does_not_ask_given_argument() {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().try_into().unwrap();
+
+        let result = parse_asking(&config, &["test", "--type", "core"], &mut |arg| panic!("'{}' should not be asked", arg.name));
+        assert!(result.is_ok())
+    }
+
+    #[rstest(input, expected,
+        case(vec!["release", "--env", "dev"], Ok(())),
+        case(vec!["release", "--env", "dev", "--sign", "--verify"], Ok(())),
+        case(vec!["release", "--env", "prod", "--approved"], Ok(())),
+        case(
+            vec!["release", "--env", "dev", "--dry", "--publish"],
+            Err(ParseError::ConflictingArguments { argument: "dry".to_string(), conflicting: "publish".to_string() })
+        ),
+        case(vec!["release", "--env", "dev", "--dry", "--publish=false"], Ok(())),
+        case(vec!["release", "--env", "dev", "--no-dry", "--publish"], Ok(())),
+        case(vec!["release", "--env", "dev", "--json", "--yaml=false"], Ok(())),
+        case(vec!["release", "--env", "dev", "--sign=false"], Ok(())),
+        case(vec!["release", "--env", "dev", "--json", "--level=0"], Ok(())),
+        case(
+            vec!["release", "--env", "dev", "--json", "--level"],
+            Err(ParseError::ExclusiveArguments { group: "format".to_string(), first: "json".to_string(), second: "level".to_string() })
+        ),
+        case(
+            vec!["release", "--env", "dev", "--sign", "--verify=false"],
+            Err(ParseError::MissingRequiredArgument { argument: "verify".to_string(), required_by: "sign".to_string() })
+        ),
+        case(
+            vec!["release", "--env", "dev", "--sign"],
+            Err(ParseError::MissingRequiredArgument { argument: "verify".to_string(), required_by: "sign".to_string() })
+        ),
+        case(
+            vec!["release", "--env", "prod"],
+            Err(ParseError::MissingConditionalArgument { argument: "approved".to_string(), condition: "env".to_string(), value: "prod".to_string() })
+        ),
+        case(
+            vec!["release", "--env", "dev", "--json", "--yaml"],
+            Err(ParseError::ExclusiveArguments { group: "format".to_string(), first: "json".to_string(), second: "yaml".to_string() })
+        ),
+    )]
+    fn checks_relations_among_arguments(input: Vec<&str>, expected: Result<(), ParseError>) {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+cmd:
+  release:
+    args:
+      dry:
+        flag:
+        conflicts_with: [publish]
+      publish:
+        flag:
+      sign:
+        flag:
+        requires: [verify]
+      verify:
+        flag:
+      env:
+        choice: [dev, prod]
+      approved:
+        flag:
+        required_if:
+          env: prod
+      json:
+        flag:
+      yaml:
+        flag:
+      level:
+        flag:
+          count: true
+    groups:
+      format: [json, yaml, level]
+    run: echo release
+"#).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &input);
+        assert_eq!(result.map(|_| ()), expected)
+    }
+
+    #[rstest(input, name, variable, expected,
+        case(vec!["scale"], "RXE_TEST_REPLICAS_GIVEN", "3", Ok(ArgumentValue::Number(3f64))),
+        case(vec!["scale", "--replicas", "5"], "RXE_TEST_REPLICAS_OVERRIDDEN", "3", Ok(ArgumentValue::Number(5f64))),
+        case(vec!["scale"], "RXE_TEST_REPLICAS_EMPTY", "", Err(ParseError::InsufficientArgument)),
+        case(vec!["scale"], "RXE_TEST_REPLICAS_MALFORMED", "many", Err(ParseError::MalformedArgument(ValueParseError::ParseFailed(
+            IdBox::new(Box::new(NumberParseError::NumberParseFailure("many".to_string())))
+        )))),
+    )]
+    fn takes_value_from_environment_variable(input: Vec<&str>, name: &str, variable: &str, expected: Result<ArgumentValue, ParseError>) {
+        // Each case has its own variable, since the cases run in parallel.
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(&format!(r#"
+cmd:
+  scale:
+    args:
+      replicas:
+        number:
+        env: {}
+    run: echo {{replicas}}
+"#, name)).unwrap().try_into().unwrap();
+
+        std::env::set_var(name, variable);
+        let result = parse_asking(&config, &input, &mut |_| None);
+        std::env::remove_var(name);
+
+        assert_eq!(result.map(|c| c.args["replicas"].clone()), expected)
+    }
+
+    #[rstest(input, offset, message,
+        case(vec!["seek", "--offset", "-5", "--msg", "x"], -5f64, "x"),
+        case(vec!["seek", "-o", "-0.5", "--msg=-x"], -0.5, "-x"),
+        case(vec!["seek", "--offset=-5", "--msg=--"], -5f64, "--"),
+    )]
+    fn accepts_dash_prefixed_values(input: Vec<&str>, offset: f64, message: &str) {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+cmd:
+  seek:
+    args:
+      offset:
+        number:
+        short: o
+      msg:
+        text:
+    run: seek {offset} {msg}
+"#).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &input).unwrap();
+        assert_eq!(result.args["offset"], ArgumentValue::Number(offset));
+        assert_eq!(result.args["msg"], ArgumentValue::Text(message.to_string()));
+    }
+
+    #[rstest(input, expected,
+        case(vec!["deploy", "@prod"], ("production", 5f64)),
+        case(vec!["deploy", "@prod", "--replicas", "2"], ("production", 2f64)),
+        case(vec!["deploy", "--replicas", "2", "@prod"], ("production", 5f64)),
+        case(vec!["deploy", "@tests/deploy.args"], ("staging", 3f64)),
+        case(vec!["deploy", "--env", "@alice", "--replicas", "1"], ("@alice", 1f64)),
+        case(vec!["deploy", "--env", "@prod", "@prod"], ("production", 5f64)),
+    )]
+    fn expands_presets_and_argument_files(input: Vec<&str>, expected: (&str, f64)) {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+cmd:
+  deploy:
+    args:
+      env:
+        text:
+      replicas:
+        number:
+    presets:
+      prod:
+        env: production
+        replicas: 5
+    run: deploy {env} {replicas}
+"#).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &input).unwrap();
+        assert_eq!(result.args["env"], ArgumentValue::Text(expected.0.to_string()));
+        assert_eq!(result.args["replicas"], ArgumentValue::Number(expected.1));
+    }
+
+    #[test]
+    fn computes_choices_once_per_parse() {
+        let marker = std::env::temp_dir().join(crate::util::get_random_string());
+        let marker = marker.to_str().unwrap();
+
+        let yaml = format!(r#"
+cmd:
+  checkout:
+    args:
+      branch:
+        choice:
+          from_command: echo x >> {}; printf 'main\nfeat'
+        multi: true
+    run: git checkout {{branch}}
+"#, marker);
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(&yaml).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &["checkout", "--branch", "main", "--branch", "feat"]);
+        let runs = std::fs::read_to_string(marker).unwrap_or_default().lines().count();
+        let _ = std::fs::remove_file(marker);
+
+        assert!(result.is_ok());
+        assert_eq!(runs, 1);
+    }
+
+    #[test]
+    fn does_not_compute_choices_of_omitted_argument() {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(r#"
+cmd:
+  checkout:
+    args:
+      branch:
+        choice:
+          from_command: exit 3
+    run: git checkout {branch}
+"#).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &["checkout"]);
+        assert_eq!(result, Err(ParseError::InsufficientArgument));
+    }
+
+    #[test]
+    fn declines_unknown_preset() {
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().try_into().unwrap();
+
+        let result = parse(&config, &["test", "@nothing"]);
+        assert!(matches!(result, Err(ParseError::UnreadableArgumentFile { path, .. }) if path == "nothing"));
+    }
 }
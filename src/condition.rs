@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::constraints::Constraint;
+use crate::constraints::{date::DateConstraint, duration::DurationConstraint, size::SizeConstraint};
+use crate::domain::ArgumentValue;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConditionError {
+    NotExistingArgument(String),
+    Malformed(String),
+}
+impl Display for ConditionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConditionError::NotExistingArgument(a) => write!(f, "Not existing argument '{}' is used in the condition.", a),
+            ConditionError::Malformed(c) => write!(f, "The condition '{}' is something wrong.", c),
+        }
+    }
+}
+
+/// Evaluates the condition like `publish`, `!publish`, `env == prod` or `env != prod`.
+pub fn evaluate(condition: &str, values: &HashMap<String, ArgumentValue>) -> Result<bool, ConditionError> {
+    let condition = condition.trim();
+
+    let get_value = |name: &str| {
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(ConditionError::Malformed(condition.to_string()));
+        }
+
+        values.get(name).ok_or_else(|| ConditionError::NotExistingArgument(name.to_string()))
+    };
+
+    if let Some((name, expected)) = condition.split_once("!=") {
+        return Ok(!equals(get_value(name)?, expected.trim()));
+    }
+    if let Some((name, expected)) = condition.split_once("==") {
+        return Ok(equals(get_value(name)?, expected.trim()));
+    }
+    if let Some(name) = condition.strip_prefix('!') {
+        return Ok(!is_truthy(get_value(name)?));
+    }
+
+    Ok(is_truthy(get_value(condition)?))
+}
+
+fn is_truthy(value: &ArgumentValue) -> bool {
+    match value {
+        ArgumentValue::Text(t) => !t.is_empty(),
+        ArgumentValue::Flag(f) => *f,
+        ArgumentValue::Number(n) => *n != 0f64,
+        ArgumentValue::Choice { value, .. } => !value.is_empty(),
+        ArgumentValue::Map(m) => !m.is_empty(),
+        ArgumentValue::Duration(d) => !d.is_zero(),
+        ArgumentValue::Size(s) => *s != 0,
+        ArgumentValue::Date(_) => true,
+    }
+}
+
+/// Returns whether the value is the expected one written in the configuration, like `prod` or `5m`.
+pub fn equals(value: &ArgumentValue, expected: &str) -> bool {
+    let expected = expected.trim_matches(|c| c == '"' || c == '\'');
+
+    match value {
+        ArgumentValue::Text(t) => t == expected,
+        ArgumentValue::Flag(f) => expected.parse::<bool>() == Ok(*f),
+        ArgumentValue::Number(n) => expected.parse::<f64>() == Ok(*n),
+        ArgumentValue::Choice { key, value } => key == expected || value == expected,
+        // The map is compared as the list of the pairs like `a=1 b=2`.
+        ArgumentValue::Map(m) => m.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(" ") == expected,
+        // These are compared after the expected value is parsed in the same way, so that `5m == 300s` holds.
+        ArgumentValue::Duration(_) => DurationConstraint.parse_value(Some(expected)).as_ref() == Ok(value),
+        ArgumentValue::Size(_) => SizeConstraint.parse_value(Some(expected)).as_ref() == Ok(value),
+        ArgumentValue::Date(_) => DateConstraint.parse_value(Some(expected)).as_ref() == Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use crate::{map, domain::ArgumentValue};
+
+    use super::{evaluate, ConditionError};
+
+    #[rstest(condition, expected,
+        case("publish", true),
+        case("!publish", false),
+        case(" dry ", false),
+        case("env == prod", true),
+        case("env==\"prod\"", true),
+        case("env != prod", false),
+        case("replicas == 3", true),
+        case("replicas", true),
+        case("dry == false", true),
+        case("target == stg", true),
+        case("target == staging", true),
+        case("timeout == 5m", true),
+        case("timeout != 300", false),
+        case("limit == 1KiB", true),
+        case("limit == 1KB", false),
+    )]
+    fn evaluates_condition(condition: &str, expected: bool) {
+        let values = map!(
+            "publish".to_string() => ArgumentValue::Flag(true),
+            "dry".to_string() => ArgumentValue::Flag(false),
+            "env".to_string() => ArgumentValue::Text("prod".to_string()),
+            "replicas".to_string() => ArgumentValue::Number(3f64),
+            "target".to_string() => ArgumentValue::Choice { key: "stg".to_string(), value: "staging".to_string() },
+            "timeout".to_string() => ArgumentValue::Duration(std::time::Duration::from_secs(300)),
+            "limit".to_string() => ArgumentValue::Size(1024),
+        );
+
+        assert_eq!(evaluate(condition, &values), Ok(expected));
+    }
+
+    #[rstest(condition, expected,
+        case("unknown", ConditionError::NotExistingArgument("unknown".to_string())),
+        case("", ConditionError::Malformed("".to_string())),
+        case("a b == c", ConditionError::Malformed("a b == c".to_string())),
+    )]
+    fn declines_incorrect_condition(condition: &str, expected: ConditionError) {
+        let values = map!("a".to_string() => ArgumentValue::Flag(true));
+
+        assert_eq!(evaluate(condition, &values), Err(expected));
+    }
+}
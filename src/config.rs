@@ -1,15 +1,34 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use std::fs;
 use std::io::{self, ErrorKind};
+use std::marker::PhantomData;
 
-use serde::{Serialize, Deserialize};
+use regex::Regex;
+use serde::{Serialize, Deserialize, Deserializer};
+use serde::de::{MapAccess, Visitor};
 
-use crate::domain::{Constraints, Command, Argument, Config};
+use crate::constraints::choice::ChoiceConstraint;
+use crate::dependency::{check_dependencies, DependencyError};
+use crate::placeholder::{collect_properties, collect_single_quoted_arguments};
+use crate::domain::{ArgumentGroup, ArgumentRelations, Preset, Choice, ChoiceMatch, ChoiceOptions, ChoiceSource, Constraints, Command, FlagOptions, Argument, Config, Dependency, MapOptions, NumberOptions, PathKind, PathOptions, Step, TextOptions};
 
+#[derive(Debug)]
 pub enum ReadError {
     FileNotExist,
     MalformedConfig(serde_yaml::Error),
+    InvalidDependency(DependencyError),
+    InvalidPattern { argument: String, error: regex::Error },
+    InvalidRange { argument: String, min: f64, max: f64 },
+    ChoiceOptionForNonChoice { argument: String, option: String },
+    UnknownChoiceBranch { command: String, argument: String, branch: String },
+    SingleQuotedSecret { command: String, argument: String },
+    UnknownRelatedArgument { command: String, argument: String },
+    UnknownPresetArgument { command: String, preset: String, argument: String },
+    UnknownSharedArgument { command: String, name: String },
+    UnknownBaseCommand { command: String, base: String },
+    ExtendsWithoutRun { command: String, base: String },
+    CyclicExtends(Vec<String>),
     IOError(io::Error)
 }
 impl From<io::Error> for ReadError {
@@ -25,37 +44,251 @@ impl From<serde_yaml::Error> for ReadError {
         ReadError::MalformedConfig(e)
     }
 }
+impl From<DependencyError> for ReadError {
+    fn from(e: DependencyError) -> Self {
+        ReadError::InvalidDependency(e)
+    }
+}
 impl Display for ReadError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ReadError::FileNotExist => write!(f, "The file not exist!"),
             ReadError::MalformedConfig(e) => write!(f, "The config is malformed: {}", e),
+            ReadError::InvalidDependency(e) => write!(f, "The dependency is invalid: {}", e),
+            ReadError::InvalidPattern { argument, error } => write!(f, "The pattern of the argument '{}' is invalid: {}", argument, error),
+            ReadError::InvalidRange { argument, min, max } => {
+                write!(f, "The range of the argument '{}' is empty, since its min {} is larger than its max {}", argument, min, max)
+            },
+            ReadError::ChoiceOptionForNonChoice { argument, option } => {
+                write!(f, "The argument '{}' has '{}', which is only available for the choice type", argument, option)
+            },
+            ReadError::UnknownChoiceBranch { command, argument, branch } => {
+                write!(f, "The placeholder of the argument '{}' in the command '{}' has the branch '{}', which is not in the choices", argument, command, branch)
+            },
+            ReadError::SingleQuotedSecret { command, argument } => {
+                write!(f, "The placeholder of the secret argument '{}' in the command '{}' is in the single quotes, where it cannot be expanded", argument, command)
+            },
+            ReadError::UnknownRelatedArgument { command, argument } => {
+                write!(f, "The relation among the arguments of the command '{}' refers to '{}', which is not the argument of the command", command, argument)
+            },
+            ReadError::UnknownPresetArgument { command, preset, argument } => {
+                write!(f, "The preset '{}' of the command '{}' has '{}', which is not the argument of the command", preset, command, argument)
+            },
+            ReadError::UnknownSharedArgument { command, name } => {
+                write!(f, "The command '{}' uses '{}', which is neither in the top-level `args` nor in `arg_sets`", command, name)
+            },
+            ReadError::UnknownBaseCommand { command, base } => write!(f, "The command '{}' extends '{}', which does not exist", command, base),
+            ReadError::ExtendsWithoutRun { command, base } => {
+                write!(f, "The command '{}' extends '{}', but has no `run`, which is not inherited", command, base)
+            },
+            ReadError::CyclicExtends(chain) => write!(f, "The commands extend each other: {}", chain.join(" -> ")),
             ReadError::IOError(e) => write!(f, "Unexpected I/O Error occurred: {}", e)
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum DeserializedConstraint {
-    Text,
-    Flag,
-    Number,
-    Choice(Vec<String>)
+    Text(Option<DeserializedTextOptions>),
+    Flag(Option<DeserializedFlagOptions>),
+    Number(Option<DeserializedNumberOptions>),
+    Choice(DeserializedChoices),
+    Path(Option<DeserializedPathOptions>),
+    Map(Option<DeserializedMapOptions>),
+    Duration,
+    Size,
+    Date
 }
 
 impl From<DeserializedConstraint> for Constraints {
     fn from(desr: DeserializedConstraint) -> Self {
         match desr {
-            DeserializedConstraint::Text => Constraints::Text,
-            DeserializedConstraint::Flag => Constraints::Flag,
-            DeserializedConstraint::Number => Constraints::Number,
-            DeserializedConstraint::Choice(v) => Constraints::Choice(v)
+            DeserializedConstraint::Text(o) => Constraints::Text(o.map(Into::into).unwrap_or_default()),
+            DeserializedConstraint::Flag(o) => Constraints::Flag(o.map(Into::into).unwrap_or_default()),
+            DeserializedConstraint::Number(o) => Constraints::Number(o.map(Into::into).unwrap_or_default()),
+            DeserializedConstraint::Choice(c) => Constraints::Choice(ChoiceSource::from(c).into()),
+            DeserializedConstraint::Path(o) => Constraints::Path(o.map(Into::into).unwrap_or_default()),
+            DeserializedConstraint::Map(o) => Constraints::Map(o.map(Into::into).unwrap_or_default()),
+            DeserializedConstraint::Duration => Constraints::Duration,
+            DeserializedConstraint::Size => Constraints::Size,
+            DeserializedConstraint::Date => Constraints::Date
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum DeserializedChoiceDetail {
+    Value(String),
+    Detailed {
+        value: Option<String>,
+        help: Option<String>
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeserializedChoiceCommand {
+    from_command: String
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeserializedChoiceGlob {
+    from_glob: String,
+    #[serde(default)]
+    strip_ext: bool
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum DeserializedChoices {
+    FromCommand(DeserializedChoiceCommand),
+    FromGlob(DeserializedChoiceGlob),
+    Keys(Vec<String>),
+    Mapped(#[serde(deserialize_with = "deserialize_ordered_map")] Vec<(String, Option<DeserializedChoiceDetail>)>)
+}
+impl From<DeserializedChoices> for ChoiceSource {
+    fn from(desr: DeserializedChoices) -> ChoiceSource {
+        match desr {
+            DeserializedChoices::FromCommand(c) => ChoiceSource::Command(c.from_command),
+            DeserializedChoices::FromGlob(g) => ChoiceSource::Glob { pattern: g.from_glob, strip_ext: g.strip_ext },
+            DeserializedChoices::Keys(keys) => ChoiceSource::Static(keys.into_iter().map(Into::into).collect()),
+            DeserializedChoices::Mapped(choices) => ChoiceSource::Static(choices.into_iter()
+                .map(|(key, detail)| match detail {
+                    None => Choice { key, value: None, help: None },
+                    Some(DeserializedChoiceDetail::Value(value)) => Choice { key, value: Some(value), help: None },
+                    Some(DeserializedChoiceDetail::Detailed { value, help }) => Choice { key, value, help },
+                })
+                .collect())
+        }
+    }
+}
+
+/// Deserializes the map into the list of the entries, keeping the order written in the config.
+fn deserialize_ordered_map<'de, D, V>(deserializer: D) -> Result<Vec<(String, V)>, D::Error>
+    where D: Deserializer<'de>,
+          V: Deserialize<'de>
+{
+    struct OrderedMapVisitor<V>(PhantomData<V>);
+    impl<'de, V: Deserialize<'de>> Visitor<'de> for OrderedMapVisitor<V> {
+        type Value = Vec<(String, V)>;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            write!(f, "a map")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::new();
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_map(OrderedMapVisitor(PhantomData))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeserializedTextOptions {
+    pattern: Option<String>,
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+}
+impl From<DeserializedTextOptions> for TextOptions {
+    fn from(desr: DeserializedTextOptions) -> Self {
+        TextOptions { pattern: desr.pattern, min_len: desr.min_len, max_len: desr.max_len }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeserializedFlagOptions {
+    #[serde(default)]
+    default: bool,
+
+    #[serde(default)]
+    count: bool,
+}
+impl From<DeserializedFlagOptions> for FlagOptions {
+    fn from(desr: DeserializedFlagOptions) -> Self {
+        FlagOptions { default: desr.default, count: desr.count }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeserializedNumberOptions {
+    min: Option<f64>,
+    max: Option<f64>,
+    step: Option<f64>,
+
+    #[serde(default)]
+    integer: bool,
+}
+impl From<DeserializedNumberOptions> for NumberOptions {
+    fn from(desr: DeserializedNumberOptions) -> Self {
+        NumberOptions { min: desr.min, max: desr.max, step: desr.step, integer: desr.integer }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeserializedMapOptions {
+    #[serde(default)]
+    keys: Vec<String>,
+}
+impl From<DeserializedMapOptions> for MapOptions {
+    fn from(desr: DeserializedMapOptions) -> Self {
+        MapOptions { keys: desr.keys }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DeserializedPathKind {
+    File,
+    Dir
+}
+impl From<DeserializedPathKind> for PathKind {
+    fn from(desr: DeserializedPathKind) -> Self {
+        match desr {
+            DeserializedPathKind::File => PathKind::File,
+            DeserializedPathKind::Dir => PathKind::Dir
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeserializedPathOptions {
+    #[serde(default)]
+    must_exist: bool,
+
+    kind: Option<DeserializedPathKind>,
+
+    #[serde(default)]
+    extensions: Vec<String>,
+
+    #[serde(default)]
+    absolute: bool,
+}
+impl From<DeserializedPathOptions> for PathOptions {
+    fn from(desr: DeserializedPathOptions) -> Self {
+        PathOptions {
+            must_exist: desr.must_exist,
+            kind: desr.kind.map(Into::into),
+            extensions: desr.extensions.into_iter().map(|e| e.trim_start_matches('.').to_string()).collect(),
+            absolute: desr.absolute
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct DeserializedArgument {
     #[serde(flatten)]
     constraint: DeserializedConstraint,
@@ -63,75 +296,795 @@ pub struct DeserializedArgument {
 
     #[serde(default)]
     multi: bool,
+
+    #[serde(default)]
+    secret: bool,
+
+    env: Option<String>,
+
+    #[serde(rename = "match")]
+    matching: Option<DeserializedChoiceMatch>,
+    case_sensitive: Option<bool>,
+
+    #[serde(default)]
+    conflicts_with: Vec<String>,
+
+    #[serde(default)]
+    requires: Vec<String>,
+
+    #[serde(default)]
+    required_if: BTreeMap<String, String>,
+}
+impl DeserializedArgument {
+    /// Returns the option only for the choice type, if it is given to the argument of the other type.
+    fn misplaced_choice_option(&self) -> Option<&'static str> {
+        if matches!(self.constraint, DeserializedConstraint::Choice(_)) {
+            return None;
+        }
+
+        match (&self.matching, &self.case_sensitive) {
+            (Some(_), _) => Some("match"),
+            (_, Some(_)) => Some("case_sensitive"),
+            _ => None
+        }
+    }
 }
 impl From<(String, DeserializedArgument)> for Argument {
     fn from(desr: (String, DeserializedArgument)) -> Argument {
         let (name, arg) = desr;
 
-        Argument {name, short_hand: arg.short, constraint: arg.constraint.into(), multi: arg.multi }
+        let mut constraint: Constraints = arg.constraint.into();
+        if let Constraints::Choice(options) = &mut constraint {
+            options.matching = arg.matching.map(Into::into).unwrap_or_default();
+            options.case_sensitive = arg.case_sensitive.unwrap_or(true);
+        }
+
+        let relations = ArgumentRelations {
+            conflicts_with: arg.conflicts_with,
+            requires: arg.requires,
+            required_if: arg.required_if.into_iter().collect()
+        };
+
+        Argument {name, short_hand: arg.short, constraint, multi: arg.multi, secret: arg.secret, env: arg.env, relations }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DeserializedChoiceMatch {
+    Exact,
+    Prefix,
+    Fuzzy
+}
+impl From<DeserializedChoiceMatch> for ChoiceMatch {
+    fn from(desr: DeserializedChoiceMatch) -> ChoiceMatch {
+        match desr {
+            DeserializedChoiceMatch::Exact => ChoiceMatch::Exact,
+            DeserializedChoiceMatch::Prefix => ChoiceMatch::Prefix,
+            DeserializedChoiceMatch::Fuzzy => ChoiceMatch::Fuzzy,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeserializedDependencyValue {
+    Flag(bool),
+    Number(f64),
+    Text(String),
+    Map(BTreeMap<String, String>)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeserializedDependency {
+    Name(String),
+    WithArgs {
+        cmd: String,
+        #[serde(default)]
+        args: BTreeMap<String, DeserializedDependencyValue>
+    }
+}
+impl From<DeserializedDependency> for Dependency {
+    fn from(desr: DeserializedDependency) -> Dependency {
+        match desr {
+            DeserializedDependency::Name(name) => Dependency { name, args: Vec::new() },
+            DeserializedDependency::WithArgs { cmd, args } => {
+                let args = args.into_iter()
+                    .flat_map(|(name, value)| {
+                        let option = format!("--{}", name);
+                        match value {
+                            DeserializedDependencyValue::Flag(true) => vec![option],
+                            DeserializedDependencyValue::Flag(false) => vec![format!("{}=false", option)],
+                            DeserializedDependencyValue::Number(n) => vec![format!("{}={}", option, n)],
+                            DeserializedDependencyValue::Text(t) => vec![format!("{}={}", option, t)],
+                            DeserializedDependencyValue::Map(m) => m.into_iter().map(|(k, v)| format!("{}={}={}", option, k, v)).collect(),
+                        }
+                    })
+                    .collect();
+
+                Dependency { name: cmd, args }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeserializedStep {
+    Script(String),
+    Detailed {
+        name: Option<String>,
+        run: String,
+        shell: Option<String>,
+        cwd: Option<String>,
+
+        #[serde(default)]
+        env: HashMap<String, String>,
+
+        #[serde(rename = "if")]
+        condition: Option<String>,
+
+        #[serde(default)]
+        continue_on_error: bool,
+    }
+}
+impl From<DeserializedStep> for Step {
+    fn from(desr: DeserializedStep) -> Step {
+        match desr {
+            DeserializedStep::Script(run) => Step::new(run),
+            DeserializedStep::Detailed { name, run, shell, cwd, env, condition, continue_on_error } => {
+                Step { name, run, shell, cwd, env, condition, continue_on_error }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeserializedRun {
+    Script(String),
+    Steps(Vec<DeserializedStep>)
+}
+impl Default for DeserializedRun {
+    fn default() -> Self {
+        DeserializedRun::Steps(Vec::new())
+    }
+}
+impl From<DeserializedRun> for Vec<Step> {
+    fn from(desr: DeserializedRun) -> Vec<Step> {
+        match desr {
+            DeserializedRun::Script(run) => vec![Step::new(run)],
+            DeserializedRun::Steps(steps) => steps.into_iter().map(Into::into).collect()
+        }
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct DeserializedCommand {
+    #[serde(default)]
     args: HashMap<String, DeserializedArgument>,
-    run: String
+
+    #[serde(default)]
+    needs: Vec<DeserializedDependency>,
+
+    #[serde(default)]
+    run: DeserializedRun,
+
+    #[serde(default)]
+    finally: DeserializedRun,
+
+    #[serde(default)]
+    groups: BTreeMap<String, Vec<String>>,
+
+    #[serde(default)]
+    presets: BTreeMap<String, BTreeMap<String, String>>,
+
+    confirm: Option<String>,
+
+    /// The shell and the environment variables for every step, which the step can override.
+    shell: Option<String>,
+
+    #[serde(default)]
+    env: HashMap<String, String>,
+
+    /// The names of the shared arguments or the argument sets.
+    #[serde(default, rename = "use")]
+    uses: Vec<String>,
+
+    extends: Option<String>
 }
 impl From<(String, DeserializedCommand)> for Command {
     fn from(desr: (String, DeserializedCommand)) -> Command {
         let (name, cmd) = desr;
 
-        Command { name, args: cmd.args.into_iter().map(Into::into).collect(), run: cmd.run }
+        let apply_defaults = |mut step: Step| {
+            step.shell = step.shell.or_else(|| cmd.shell.clone());
+            step.env = cmd.env.clone().into_iter().chain(step.env).collect();
+            step
+        };
+        let run = Vec::<Step>::from(cmd.run).into_iter().map(apply_defaults).collect();
+        let finally = Vec::<Step>::from(cmd.finally).into_iter().map(apply_defaults).collect();
+
+        Command {
+            name,
+            args: cmd.args.into_iter().map(Into::into).collect(),
+            needs: cmd.needs.into_iter().map(Into::into).collect(),
+            run,
+            finally,
+            groups: cmd.groups.into_iter().map(|(name, args)| ArgumentGroup { name, args }).collect(),
+            presets: cmd.presets.into_iter().map(|(name, args)| Preset { name, args: args.into_iter().collect() }).collect(),
+            confirm: cmd.confirm
+        }
     }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct DeserializedConfig {
-    cmd: HashMap<String, DeserializedCommand>
+    cmd: HashMap<String, DeserializedCommand>,
+
+    /// The arguments which the commands can use by their name.
+    #[serde(default)]
+    args: HashMap<String, DeserializedArgument>,
+
+    #[serde(default)]
+    arg_sets: HashMap<String, HashMap<String, DeserializedArgument>>,
+
+    #[serde(default)]
+    before: DeserializedRun,
+
+    #[serde(default)]
+    after: DeserializedRun
 }
-impl From<DeserializedConfig> for Config {
-    fn from(desr: DeserializedConfig) -> Config {
-        Config { command: desr.cmd.into_iter().map(Into::into).collect() }
+/// Converts the config after `use` and `extends` are resolved, which would be lost otherwise.
+impl TryFrom<DeserializedConfig> for Config {
+    type Error = ReadError;
+
+    fn try_from(desr: DeserializedConfig) -> Result<Config, ReadError> {
+        desr.check_choice_options()?;
+        let desr = desr.resolve_shared()?;
+
+        Ok(Config {
+            command: desr.cmd.into_iter().map(Into::into).collect(),
+            before: desr.before.into(),
+            after: desr.after.into()
+        })
+    }
+}
+
+impl DeserializedConfig {
+    /// Checks that the options only for the choice type are not given to the other types.
+    fn check_choice_options(&self) -> Result<(), ReadError> {
+        let arguments = self.cmd.values().flat_map(|c| &c.args)
+            .chain(&self.args)
+            .chain(self.arg_sets.values().flatten());
+
+        for (name, arg) in arguments {
+            if let Some(option) = arg.misplaced_choice_option() {
+                return Err(ReadError::ChoiceOptionForNonChoice { argument: name.clone(), option: option.to_string() });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expands `use` and `extends` of the commands, so that every command has all of its arguments.
+    fn resolve_shared(mut self) -> Result<Self, ReadError> {
+        let mut pending = std::mem::take(&mut self.cmd);
+        let mut resolved = HashMap::new();
+
+        while let Some(name) = pending.keys().next().cloned() {
+            self.resolve_command(&name, &mut pending, &mut resolved, &mut Vec::new())?;
+        }
+
+        self.cmd = resolved;
+        Ok(self)
+    }
+
+    /// Resolves the command after its base, moving it from `pending` to `resolved`.
+    /// `chain` is the commands being resolved, which extend each other in that order.
+    fn resolve_command(
+        &self,
+        name: &str,
+        pending: &mut HashMap<String, DeserializedCommand>,
+        resolved: &mut HashMap<String, DeserializedCommand>,
+        chain: &mut Vec<String>
+    ) -> Result<(), ReadError> {
+        let mut cmd = pending.remove(name).expect("The command should be pending");
+        chain.push(name.to_string());
+
+        let mut args = HashMap::new();
+        let mut env = HashMap::new();
+        let mut shell = None;
+
+        if let Some(base) = cmd.extends.take() {
+            // The command would run nothing, since `run` is not inherited from the base.
+            if matches!(&cmd.run, DeserializedRun::Steps(s) if s.is_empty()) {
+                return Err(ReadError::ExtendsWithoutRun { command: name.to_string(), base });
+            }
+            if chain.contains(&base) {
+                chain.push(base);
+                return Err(ReadError::CyclicExtends(chain.clone()));
+            }
+            if pending.contains_key(&base) {
+                self.resolve_command(&base, pending, resolved, chain)?;
+            }
+
+            let base = resolved.get(&base).ok_or_else(|| ReadError::UnknownBaseCommand { command: name.to_string(), base })?;
+            args = base.args.clone();
+            env = base.env.clone();
+            shell = base.shell.clone();
+        }
+
+        for used in std::mem::take(&mut cmd.uses) {
+            match (self.arg_sets.get(&used), self.args.get(&used)) {
+                (Some(set), _) => args.extend(set.clone()),
+                (None, Some(arg)) => { args.insert(used, arg.clone()); },
+                (None, None) => return Err(ReadError::UnknownSharedArgument { command: name.to_string(), name: used })
+            }
+        }
+
+        // What the command itself defines always wins.
+        args.extend(std::mem::take(&mut cmd.args));
+        env.extend(std::mem::take(&mut cmd.env));
+        cmd.args = args;
+        cmd.env = env;
+        cmd.shell = cmd.shell.or(shell);
+
+        chain.pop();
+        resolved.insert(name.to_string(), cmd);
+        Ok(())
     }
 }
 
 pub fn read_from_yaml(path: &str) -> Result<Config, ReadError> {
     let content: Result<String, ReadError> = fs::read_to_string(path).map_err(Into::into);
     let config: Result<DeserializedConfig, ReadError> = serde_yaml::from_str::<DeserializedConfig>(&content?).map_err(Into::into);
+    let config: Config = config?.try_into()?;
 
-    Ok(config?.into())
+    check_dependencies(&config)?;
+    check_patterns(&config)?;
+    check_ranges(&config)?;
+    check_choice_branches(&config)?;
+    check_secret_quotes(&config)?;
+    check_relations(&config)?;
+    check_presets(&config)?;
+
+    Ok(config)
+}
+
+fn check_patterns(config: &Config) -> Result<(), ReadError> {
+    let arguments = config.command.iter().flat_map(|c| &c.args);
+    for arg in arguments {
+        if let Constraints::Text(TextOptions { pattern: Some(pattern), .. }) = &arg.constraint {
+            Regex::new(pattern).map_err(|error| ReadError::InvalidPattern { argument: arg.name.clone(), error })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn check_ranges(config: &Config) -> Result<(), ReadError> {
+    let arguments = config.command.iter().flat_map(|c| &c.args);
+    for arg in arguments {
+        if let Constraints::Number(NumberOptions { min: Some(min), max: Some(max), .. }) = &arg.constraint {
+            if min > max {
+                return Err(ReadError::InvalidRange { argument: arg.name.clone(), min: *min, max: *max });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_relations(config: &Config) -> Result<(), ReadError> {
+    for cmd in &config.command {
+        let related = cmd.args.iter()
+            .flat_map(|a| {
+                let relations = &a.relations;
+                relations.conflicts_with.iter()
+                    .chain(&relations.requires)
+                    .chain(relations.required_if.iter().map(|(name, _)| name))
+            })
+            .chain(cmd.groups.iter().flat_map(|g| &g.args));
+
+        for name in related {
+            if cmd.get_argument(name).is_none() {
+                return Err(ReadError::UnknownRelatedArgument { command: cmd.name.clone(), argument: name.clone() });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_presets(config: &Config) -> Result<(), ReadError> {
+    for cmd in &config.command {
+        for preset in &cmd.presets {
+            if let Some((argument, _)) = preset.args.iter().find(|(name, _)| cmd.get_argument(name).is_none()) {
+                return Err(ReadError::UnknownPresetArgument {
+                    command: cmd.name.clone(),
+                    preset: preset.name.clone(),
+                    argument: argument.clone()
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_choice_branches(config: &Config) -> Result<(), ReadError> {
+    for cmd in &config.command {
+        let steps = cmd.run.iter().chain(&cmd.finally).chain(&config.before).chain(&config.after);
+        let lines = steps
+            .flat_map(|s| [Some(&s.run), s.cwd.as_ref()].into_iter().flatten().chain(s.env.values()))
+            .chain(&cmd.confirm)
+            .collect::<Vec<_>>();
+
+        for arg in &cmd.args {
+            // The choices computed from the command or the glob are not known until they are used.
+            let choices = match &arg.constraint {
+                Constraints::Choice(ChoiceOptions { source: ChoiceSource::Static(c), .. }) => ChoiceConstraint::new(c.clone()),
+                _ => continue
+            };
+
+            // The malformed placeholder is reported when it is filled, so it is not checked here.
+            let properties = lines.iter().flat_map(|l| collect_properties(l, &arg.name).unwrap_or_default());
+            for property in properties {
+                if !choices.is_available_property(&property) {
+                    return Err(ReadError::UnknownChoiceBranch {
+                        command: cmd.name.clone(),
+                        argument: arg.name.clone(),
+                        branch: property
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The secret values are passed to the script through the environment variables, which are not expanded in the single quotes.
+fn check_secret_quotes(config: &Config) -> Result<(), ReadError> {
+    for cmd in &config.command {
+        let steps = cmd.run.iter().chain(&cmd.finally).chain(&config.before).chain(&config.after);
+        for step in steps {
+            // The malformed placeholder is reported when it is filled, so it is not checked here.
+            let quoted = collect_single_quoted_arguments(&step.run).unwrap_or_default();
+            if let Some(arg) = cmd.args.iter().find(|a| a.secret && quoted.contains(&a.name)) {
+                return Err(ReadError::SingleQuotedSecret { command: cmd.name.clone(), argument: arg.name.clone() });
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::domain::{Config, Constraints, Argument};
+    use rstest::rstest;
+
+    use crate::domain::{Config, Constraints, Argument, ArgumentRelations, Choice, ChoiceMatch, ChoiceOptions, ChoiceSource, FlagOptions};
 
-    use super::DeserializedConfig;
+    use super::{check_choice_branches, check_presets, check_ranges, check_relations, check_secret_quotes, DeserializedConfig, ReadError};
 
     #[test]
     fn accept_correct_yaml_content() {
-        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().into();
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(include_str!("../tests/acceptable_config.yaml")).unwrap().try_into().unwrap();
 
         let expected: Vec<Argument> = vec![
             Argument {
                 name: "type".to_string(),
-                constraint: Constraints::Choice(vec!["core".to_string(), "frontend".to_string(), "types".to_string()]),
+                constraint: Constraints::Choice(ChoiceSource::Static(vec!["core".to_string().into(), "frontend".to_string().into(), "types".to_string().into()]).into()),
                 short_hand: Some("t".to_string()),
-                multi: true
+                multi: true,
+                secret: false,
+                env: None,
+                relations: ArgumentRelations::default()
             },
             Argument {
                 name: "snapshot".to_string(),
-                constraint: Constraints::Flag,
+                constraint: Constraints::Flag(FlagOptions::default()),
+                short_hand: None,
+                multi: false,
+                secret: false,
+                env: None,
+                relations: ArgumentRelations::default()
+            },
+            Argument {
+                name: "cache".to_string(),
+                constraint: Constraints::Flag(FlagOptions { default: true, ..FlagOptions::default() }),
                 short_hand: None,
-                multi: false
+                multi: false,
+                secret: false,
+                env: None,
+                relations: ArgumentRelations::default()
             }
         ];
 
-        let cmd = config.command.get(0).expect("command length was 0");
+        let cmd = config.command.first().expect("command length was 0");
         for arg in &cmd.args {
-            let expected_arg = expected.iter().find(|e| e.name == arg.name).expect(&format!("Extraneous argument: {}", arg.name));
+            let expected_arg = expected.iter().find(|e| e.name == arg.name).unwrap_or_else(|| panic!("Extraneous argument: {}", arg.name));
 
             assert_eq!(arg, expected_arg);
         }
     }
+
+    #[test]
+    fn accept_mapped_choices_in_order() {
+        let yaml = r#"
+cmd:
+  deploy:
+    args:
+      env:
+        choice:
+          stg: staging
+          prod:
+            value: production
+            help: Live cluster
+          dev:
+    run: echo {env}
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        let expected = Constraints::Choice(ChoiceSource::Static(vec![
+            Choice { key: "stg".to_string(), value: Some("staging".to_string()), help: None },
+            Choice { key: "prod".to_string(), value: Some("production".to_string()), help: Some("Live cluster".to_string()) },
+            Choice { key: "dev".to_string(), value: None, help: None },
+        ]).into());
+        assert_eq!(config.command[0].args[0].constraint, expected);
+    }
+
+    #[test]
+    fn accept_confirmation() {
+        let yaml = r#"
+cmd:
+  deploy:
+    args:
+      env:
+        choice: [stg, prod]
+    confirm: Deploy to {env}?
+    run: echo {env}
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        assert_eq!(config.command[0].confirm, Some("Deploy to {env}?".to_string()));
+    }
+
+    #[test]
+    fn decline_relation_to_unknown_argument() {
+        let yaml = r#"
+cmd:
+  release:
+    args:
+      dry:
+        flag:
+        conflicts_with: [publish]
+    run: echo release
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        let error = check_relations(&config).expect_err("Should fail, but succeeded");
+        assert!(matches!(error, ReadError::UnknownRelatedArgument { argument, .. } if argument == "publish"));
+    }
+
+    #[test]
+    fn decline_empty_range() {
+        let yaml = r#"
+cmd:
+  serve:
+    args:
+      port:
+        number:
+          min: 10
+          max: 1
+    run: echo {port}
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        let error = check_ranges(&config).expect_err("Should fail, but succeeded");
+        assert!(matches!(error, ReadError::InvalidRange { argument, min, max } if argument == "port" && min == 10f64 && max == 1f64));
+    }
+
+    #[test]
+    fn decline_preset_with_unknown_argument() {
+        let yaml = r#"
+cmd:
+  deploy:
+    args:
+      env:
+        text:
+    presets:
+      prod:
+        environment: production
+    run: echo {env}
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        let error = check_presets(&config).expect_err("Should fail, but succeeded");
+        assert!(matches!(error, ReadError::UnknownPresetArgument { argument, .. } if argument == "environment"));
+    }
+
+    #[test]
+    fn accept_shared_arguments_and_extends() {
+        let yaml = r#"
+args:
+  verbose:
+    flag:
+arg_sets:
+  release:
+    release:
+      flag:
+    target:
+      text:
+cmd:
+  base:
+    use: [verbose]
+    args:
+      env:
+        choice: [dev, prod]
+    shell: bash -eu
+    env:
+      REGION: tokyo
+      STAGE: base
+    run: echo base
+  deploy:
+    extends: base
+    use: [release]
+    args:
+      target:
+        number:
+    env:
+      STAGE: deploy
+    run:
+      - echo {env}
+      - run: echo done
+        shell: sh
+"#;
+        let config = Config::try_from(serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap())
+            .unwrap_or_else(|e| panic!("Should success, but failed: {}", e));
+        let deploy = config.get_command("deploy").unwrap();
+
+        let mut names = deploy.args.iter().map(|a| a.name.as_str()).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["env", "release", "target", "verbose"]);
+        assert!(matches!(deploy.get_argument("target").unwrap().constraint, Constraints::Number(_)));
+
+        assert_eq!(deploy.run[0].run, "echo {env}");
+        assert_eq!(deploy.run[0].shell.as_deref(), Some("bash -eu"));
+        assert_eq!(deploy.run[1].shell.as_deref(), Some("sh"));
+        assert_eq!(deploy.run[0].env.get("REGION").map(String::as_str), Some("tokyo"));
+        assert_eq!(deploy.run[0].env.get("STAGE").map(String::as_str), Some("deploy"));
+    }
+
+    #[rstest(yaml, expected,
+        case("cmd: {a: {use: [nothing], run: echo}}", "ReadError::UnknownSharedArgument"),
+        case("cmd: {a: {extends: nothing, run: echo}}", "ReadError::UnknownBaseCommand"),
+        case("cmd: {a: {extends: b, run: echo}, b: {extends: a, run: echo}}", "ReadError::CyclicExtends"),
+        case("cmd: {a: {extends: a, run: echo}}", "ReadError::CyclicExtends"),
+        case("cmd: {a: {extends: b}, b: {run: echo}}", "ReadError::ExtendsWithoutRun"),
+        case("cmd: {a: {extends: b, run: []}, b: {run: echo}}", "ReadError::ExtendsWithoutRun"),
+    )]
+    fn decline_incorrect_sharing(yaml: &str, expected: &str) {
+        let error = Config::try_from(serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap()).expect_err("Should fail, but succeeded");
+
+        let identifier = match error {
+            ReadError::UnknownSharedArgument { .. } => "ReadError::UnknownSharedArgument",
+            ReadError::UnknownBaseCommand { .. } => "ReadError::UnknownBaseCommand",
+            ReadError::CyclicExtends(_) => "ReadError::CyclicExtends",
+            ReadError::ExtendsWithoutRun { .. } => "ReadError::ExtendsWithoutRun",
+            _ => panic!("Unexpected error yielded: {}", error)
+        };
+        assert_eq!(identifier, expected);
+    }
+
+    #[rstest(run, accepted,
+        case("login --token {token}", true),
+        case(r#"login --token "{token}""#, true),
+        case("login --user '{user}'", true),
+        case("login --token '{token}'", false),
+        case("echo 'token: {token}'", false),
+    )]
+    fn decline_single_quoted_secret(run: &str, accepted: bool) {
+        let yaml = format!(r#"
+cmd:
+  login:
+    args:
+      user:
+        text:
+      token:
+        text:
+        secret: true
+    run: "{}"
+"#, run.replace('"', r#"\""#));
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(&yaml).unwrap().try_into().unwrap();
+
+        let result = check_secret_quotes(&config);
+        match accepted {
+            true => assert!(result.is_ok()),
+            false => assert!(matches!(result, Err(ReadError::SingleQuotedSecret { argument, .. }) if argument == "token"))
+        }
+    }
+
+    #[test]
+    fn decline_branch_not_in_choices() {
+        let yaml = r#"
+cmd:
+  deploy:
+    args:
+      env:
+        choice:
+          prod: production
+          stg: staging
+    run:
+      - echo {env|production=--replicas 5|stg=--replicas 1|else=}
+      - echo {env|dev=--debug}
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        let error = check_choice_branches(&config).expect_err("Should fail, but succeeded");
+        assert!(matches!(error, ReadError::UnknownChoiceBranch { branch, .. } if branch == "dev"));
+    }
+
+    #[test]
+    fn accept_dynamic_choices() {
+        let yaml = r#"
+cmd:
+  deploy:
+    args:
+      branch:
+        choice:
+          from_command: git branch --format=%(refname:short)
+      service:
+        choice:
+          from_glob: deploy/*.yaml
+          strip_ext: true
+    run: echo {branch} {service}
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        let cmd = &config.command[0];
+        assert_eq!(
+            cmd.get_argument("branch").unwrap().constraint,
+            Constraints::Choice(ChoiceSource::Command("git branch --format=%(refname:short)".to_string()).into())
+        );
+        assert_eq!(
+            cmd.get_argument("service").unwrap().constraint,
+            Constraints::Choice(ChoiceSource::Glob { pattern: "deploy/*.yaml".to_string(), strip_ext: true }.into())
+        );
+    }
+
+    #[test]
+    fn accept_choice_matching_options() {
+        let yaml = r#"
+cmd:
+  deploy:
+    args:
+      env:
+        choice: [prod, stg]
+        match: fuzzy
+        case_sensitive: false
+    run: echo {env}
+"#;
+        let config: Config = serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap();
+
+        let expected = ChoiceOptions {
+            source: ChoiceSource::Static(vec!["prod".to_string().into(), "stg".to_string().into()]),
+            matching: ChoiceMatch::Fuzzy,
+            case_sensitive: false
+        };
+        assert_eq!(config.command[0].args[0].constraint, Constraints::Choice(expected));
+    }
+
+    #[rstest(yaml, expected,
+        case("cmd: {a: {args: {name: {text:, match: prefix}}, run: echo}}", "match"),
+        case("args: {name: {number:, case_sensitive: false}}\ncmd: {a: {run: echo}}", "case_sensitive"),
+    )]
+    fn decline_choice_options_for_other_types(yaml: &str, expected: &str) {
+        let error = Config::try_from(serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap()).expect_err("Should fail, but succeeded");
+
+        assert!(matches!(error, ReadError::ChoiceOptionForNonChoice { argument, option } if argument == "name" && option == expected));
+    }
 }
@@ -1,19 +1,21 @@
-use std::{fmt::Display, collections::HashMap};
+use std::{cell::RefCell, fmt::Display, collections::HashMap, process::Stdio};
 
-use crate::{domain::ArgumentValue, helper::identify::{IdBox, Identify}, placeholder::PlaceholderParseError};
+use crate::{domain::{ArgumentValue, Choice, ChoiceMatch, ChoiceOptions, ChoiceSource}, helper::identify::{IdBox, Identify}, placeholder::PlaceholderParseError, runner::shell_command};
 
 use super::{ValuefulConstraint, SpecificParseError};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ChoiceError {
     Ambiguous(String),
-    NotIncluded(String)
+    NotIncluded { value: String, choices: Vec<String> },
+    Unresolvable(String)
 }
 impl Identify for ChoiceError {
     fn get_identifier(&self) -> String {
         match self {
             ChoiceError::Ambiguous(_) => "ChoiceError::Ambiguous".to_string(),
-            ChoiceError::NotIncluded(_) => "ChoiceError::NotIncluded".to_string()
+            ChoiceError::NotIncluded { .. } => "ChoiceError::NotIncluded".to_string(),
+            ChoiceError::Unresolvable(_) => "ChoiceError::Unresolvable".to_string()
         }
     }
 }
@@ -21,54 +23,180 @@ impl Display for ChoiceError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ChoiceError::Ambiguous(cmd) => write!(f, "'{}' is too ambiguous. Type the choice longer", cmd),
-            ChoiceError::NotIncluded(cmd) => write!(f, "'{}' is not available as the choice.", cmd),
+            ChoiceError::NotIncluded { value, choices } => {
+                write!(f, "'{}' is not available as the choice. Choose from: {}", value, choices.join(", "))
+            },
+            ChoiceError::Unresolvable(reason) => write!(f, "The choices could not be computed: {}", reason),
         }
     }
 }
 impl SpecificParseError for ChoiceError {}
 
 pub struct ChoiceConstraint {
-    choices: Vec<String>
+    choices: Vec<Choice>,
+    matching: ChoiceMatch,
+    case_sensitive: bool,
 }
 impl ValuefulConstraint for ChoiceConstraint {
     fn parse_value(&self, value: &str) -> Result<ArgumentValue, IdBox<dyn SpecificParseError>> {
-        let matched: Vec<_> = self.choices.iter().filter(|c| c.starts_with(&value)).collect();
-        if matched.is_empty() {
-            return Err(IdBox::new(Box::new(ChoiceError::NotIncluded(value.to_owned()))));
-        }
-        if matched.len() > 1 {
-            return Err(IdBox::new(Box::new(ChoiceError::Ambiguous(value.to_owned()))));
-        }
+        let choice = self.find(value).map_err(|e| IdBox::new(Box::new(e) as Box<dyn SpecificParseError>))?;
 
-        Ok(ArgumentValue::Text(matched[0].to_owned()))
+        Ok(ArgumentValue::Choice {
+            key: choice.key.to_owned(),
+            value: choice.value.as_ref().unwrap_or(&choice.key).to_owned()
+        })
     }
 
-    fn fill_placeholder(&self, value: &ArgumentValue, _placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
+    fn fill_placeholder(&self, value: &ArgumentValue, placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
         match value {
-            ArgumentValue::Text(t) => Ok(t.to_string()),
+            ArgumentValue::Choice { key, .. } if placeholder_args.contains_key("key") => Ok(key.to_string()),
+            ArgumentValue::Choice { key, value } if !placeholder_args.is_empty() => {
+                // The branch is looked up by the mapped value first, and then by the key.
+                let branch = placeholder_args.get(value)
+                    .or_else(|| placeholder_args.get(key))
+                    .or_else(|| placeholder_args.get("else"));
+
+                Ok(branch.cloned().unwrap_or_default())
+            },
+            ArgumentValue::Choice { value, .. } => Ok(value.to_string()),
             _ => panic!("Unexpected ArgumentValue: {:#?}", value)
         }
     }
 }
 impl ChoiceConstraint {
-    pub fn new(choices: Vec<String>) -> Self {
-        Self { choices }
+    pub fn new(choices: Vec<Choice>) -> Self {
+        Self { choices, matching: ChoiceMatch::default(), case_sensitive: true }
+    }
+
+    pub fn from_options(options: &ChoiceOptions, choices: Vec<Choice>) -> Self {
+        Self { choices, matching: options.matching, case_sensitive: options.case_sensitive }
+    }
+
+    fn find(&self, value: &str) -> Result<&Choice, ChoiceError> {
+        let normalize = |s: &str| if self.case_sensitive { s.to_string() } else { s.to_lowercase() };
+        let input = normalize(value);
+
+        if let Some(choice) = self.choices.iter().find(|c| normalize(&c.key) == input) {
+            return Ok(choice);
+        }
+
+        let matched: Vec<_> = self.choices.iter()
+            .filter(|c| match self.matching {
+                ChoiceMatch::Exact => false,
+                ChoiceMatch::Prefix => normalize(&c.key).starts_with(&input),
+                ChoiceMatch::Fuzzy => is_subsequence(&input, &normalize(&c.key)),
+            })
+            .collect();
+
+        match matched[..] {
+            [choice] => Ok(choice),
+            [] => Err(ChoiceError::NotIncluded {
+                value: value.to_owned(),
+                choices: self.choices.iter().map(|c| c.key.clone()).collect()
+            }),
+            _ => Err(ChoiceError::Ambiguous(value.to_owned()))
+        }
+    }
+
+    /// Returns whether the property is available in the placeholder, that is, `key`, `else` or the branch for the choice.
+    pub fn is_available_property(&self, name: &str) -> bool {
+        name == "key" || name == "else" || self.choices.iter().any(|c| c.key == name || c.value.as_deref() == Some(name))
     }
 }
 
+/// Keeps the computed choices, so that the command or the glob is evaluated only once while the line is parsed.
+#[derive(Default)]
+pub struct ChoiceCache(RefCell<HashMap<ChoiceSource, Result<Vec<Choice>, ChoiceError>>>);
+impl ChoiceCache {
+    pub fn constraint(&self, options: &ChoiceOptions) -> Result<ChoiceConstraint, ChoiceError> {
+        let choices = match &options.source {
+            ChoiceSource::Static(choices) => choices.clone(),
+            source => self.0.borrow_mut()
+                .entry(source.clone())
+                .or_insert_with(|| resolve_choices(source))
+                .clone()?
+        };
+
+        Ok(ChoiceConstraint::from_options(options, choices))
+    }
+}
+
+/// Returns whether all the characters of `input` appear in `target` in the same order.
+fn is_subsequence(input: &str, target: &str) -> bool {
+    let mut target = target.chars();
+    input.chars().all(|i| target.any(|t| t == i))
+}
+
+/// Computes the choices from the source. Each line of the output or each matched file becomes the choice.
+pub fn resolve_choices(source: &ChoiceSource) -> Result<Vec<Choice>, ChoiceError> {
+    let keys = match source {
+        ChoiceSource::Static(choices) => return Ok(choices.clone()),
+        ChoiceSource::Command(command) => run_choice_command(command)?,
+        ChoiceSource::Glob { pattern, strip_ext } => match_choice_glob(pattern, *strip_ext)?,
+    };
+
+    Ok(keys.into_iter().map(Into::into).collect())
+}
+
+fn run_choice_command(command: &str) -> Result<Vec<String>, ChoiceError> {
+    let output = shell_command(command)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| ChoiceError::Unresolvable(format!("'{}' could not be run: {}", command, e)))?;
+
+    if !output.status.success() {
+        return Err(ChoiceError::Unresolvable(format!("'{}' exited with {}", command, output.status)));
+    }
+
+    Ok(
+        String::from_utf8_lossy(&output.stdout).lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(ToString::to_string)
+            .collect()
+    )
+}
+
+fn match_choice_glob(pattern: &str, strip_ext: bool) -> Result<Vec<String>, ChoiceError> {
+    let paths = glob::glob(pattern)
+        .map_err(|e| ChoiceError::Unresolvable(format!("'{}' is not the valid glob: {}", pattern, e)))?;
+
+    let mut keys = paths
+        .filter_map(Result::ok)
+        .filter_map(|p| {
+            let name = if strip_ext { p.file_stem() } else { p.file_name() };
+            name.and_then(|n| n.to_str()).map(ToString::to_string)
+        })
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    Ok(keys)
+}
+
 #[cfg(test)]
 mod tests{
+    use std::collections::HashMap;
+
     use rstest::{fixture, rstest};
-    use crate::{constraints::{Constraint, ValueParseError}, domain::ArgumentValue, helper::identify::Identify};
+    use crate::{map, constraints::{Constraint, ValueParseError}, domain::{ArgumentValue, Choice, ChoiceMatch, ChoiceOptions, ChoiceSource}, helper::identify::Identify};
 
-    use super::{ChoiceConstraint, ChoiceError};
+    use super::{resolve_choices, ChoiceCache, ChoiceConstraint, ChoiceError};
 
     #[fixture]
     pub fn constraint() -> ChoiceConstraint {
         ChoiceConstraint::new(vec![
-            "ChoiceAAA".to_string(),
-            "ChoiceBBB".to_string(),
-            "DDD".to_string(),
+            "ChoiceAAA".to_string().into(),
+            "ChoiceBBB".to_string().into(),
+            "DDD".to_string().into(),
+        ])
+    }
+
+    #[fixture]
+    pub fn mapped_constraint() -> ChoiceConstraint {
+        ChoiceConstraint::new(vec![
+            Choice { key: "prod".to_string(), value: Some("production".to_string()), help: Some("Live cluster".to_string()) },
+            Choice { key: "stg".to_string(), value: Some("staging".to_string()), help: None },
         ])
     }
 
@@ -82,11 +210,37 @@ mod tests{
         let parsed = constraint.parse_value(Some(choice));
 
         let parsed = parsed.expect("Should success, but failed");
-        assert_eq!(parsed, ArgumentValue::Text(expected.to_string()))
+        assert_eq!(parsed, ArgumentValue::Choice { key: expected.to_string(), value: expected.to_string() })
+    }
+
+    #[rstest(choice, key, value,
+        case("p", "prod", "production"),
+        case("stg", "stg", "staging"),
+    )]
+    fn maps_choice_to_value(mapped_constraint: ChoiceConstraint, choice: &str, key: &str, value: &str) {
+        let parsed = mapped_constraint.parse_value(Some(choice));
+
+        let parsed = parsed.expect("Should success, but failed");
+        assert_eq!(parsed, ArgumentValue::Choice { key: key.to_string(), value: value.to_string() })
+    }
+
+    #[rstest(properties, expected,
+        case(map!(), "production"),
+        case(map!("key" => ""), "prod"),
+        case(map!("production" => "--replicas 5", "stg" => "--replicas 1"), "--replicas 5"),
+        case(map!("prod" => "--replicas 5", "else" => "--replicas 1"), "--replicas 5"),
+        case(map!("stg" => "--replicas 1", "else" => "--dry-run"), "--dry-run"),
+        case(map!("stg" => "--replicas 1"), ""),
+    )]
+    fn fills_value_or_key(mapped_constraint: ChoiceConstraint, properties: HashMap<&str, &str>, expected: &str) {
+        let value = ArgumentValue::Choice { key: "prod".to_string(), value: "production".to_string() };
+        let properties = properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+        assert_eq!(mapped_constraint.fill_placeholder(&value, &properties), Ok(expected.to_string()))
     }
 
     #[rstest(choice, expected,
-        case("H", ChoiceError::NotIncluded("H".to_string())),
+        case("H", ChoiceError::NotIncluded { value: "H".to_string(), choices: vec![] }),
         case("", ChoiceError::Ambiguous("".to_string())),
         case("Choice", ChoiceError::Ambiguous("".to_string())),
     )]
@@ -108,4 +262,73 @@ mod tests{
         let error = parsed.expect_err("Should fail, but succeeded");
         assert_eq!(error, ValueParseError::ValueRequired)
     }
+
+    #[rstest(name, expected,
+        case("prod", true),
+        case("staging", true),
+        case("else", true),
+        case("key", true),
+        case("dev", false),
+    )]
+    fn checks_available_property(mapped_constraint: ChoiceConstraint, name: &str, expected: bool) {
+        assert_eq!(mapped_constraint.is_available_property(name), expected)
+    }
+
+    #[rstest(source, expected,
+        case(ChoiceSource::Command("printf 'main\\n\\n  feat/x  \\n'".to_string()), vec!["main", "feat/x"]),
+        case(ChoiceSource::Glob { pattern: "src/constraints/[cf]*.rs".to_string(), strip_ext: true }, vec!["choice", "flag"]),
+        case(ChoiceSource::Glob { pattern: "src/constraints/[cf]*.rs".to_string(), strip_ext: false }, vec!["choice.rs", "flag.rs"]),
+    )]
+    fn computes_choices_from_source(source: ChoiceSource, expected: Vec<&str>) {
+        let choices = resolve_choices(&source).expect("Should success, but failed");
+
+        let keys = choices.iter().map(|c| c.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, expected)
+    }
+
+    #[test]
+    fn fails_when_choice_command_fails() {
+        let choices = resolve_choices(&ChoiceSource::Command("exit 3".to_string()));
+
+        let error = choices.expect_err("Should fail, but succeeded");
+        assert_eq!(error.get_identifier(), "ChoiceError::Unresolvable")
+    }
+
+    #[rstest(matching, case_sensitive, input, expected,
+        case(ChoiceMatch::Exact, true, "ChoiceAAA", Some("ChoiceAAA")),
+        case(ChoiceMatch::Exact, true, "ChoiceA", None),
+        case(ChoiceMatch::Exact, false, "dDd", Some("DDD")),
+        case(ChoiceMatch::Prefix, false, "choiceb", Some("ChoiceBBB")),
+        case(ChoiceMatch::Prefix, true, "choiceb", None),
+        case(ChoiceMatch::Fuzzy, true, "CB", Some("ChoiceBBB")),
+        case(ChoiceMatch::Fuzzy, false, "cib", Some("ChoiceBBB")),
+        case(ChoiceMatch::Fuzzy, true, "cb", None),
+    )]
+    fn matches_choice_with_options(matching: ChoiceMatch, case_sensitive: bool, input: &str, expected: Option<&str>) {
+        let source = ChoiceSource::Static(vec![
+            "ChoiceAAA".to_string().into(),
+            "ChoiceBBB".to_string().into(),
+            "DDD".to_string().into(),
+        ]);
+        let options = ChoiceOptions { source, matching, case_sensitive };
+        let parsed = ChoiceCache::default().constraint(&options).unwrap().parse_value(Some(input));
+
+        let expected = expected.map(|e| ArgumentValue::Choice { key: e.to_string(), value: e.to_string() });
+        assert_eq!(parsed.ok(), expected)
+    }
+
+    #[test]
+    fn prefers_exact_choice_to_prefix() {
+        let constraint = ChoiceConstraint::new(vec!["core".to_string().into(), "core-ui".to_string().into()]);
+
+        let parsed = constraint.parse_value(Some("core"));
+        assert_eq!(parsed, Ok(ArgumentValue::Choice { key: "core".to_string(), value: "core".to_string() }))
+    }
+
+    #[rstest]
+    fn lists_choices_when_not_included(constraint: ChoiceConstraint) {
+        let error = constraint.find("H").expect_err("Should fail, but succeeded");
+
+        assert_eq!(error.to_string(), "'H' is not available as the choice. Choose from: ChoiceAAA, ChoiceBBB, DDD")
+    }
 }
@@ -0,0 +1,122 @@
+use std::{fmt::Display, collections::HashMap};
+
+use chrono::{Duration, Local, NaiveDate, format::{Item, StrftimeItems}};
+
+use crate::{domain::ArgumentValue, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
+
+use super::{ValuefulConstraint, SpecificParseError};
+
+#[derive(Debug)]
+pub enum DateParseError {
+    Malformed(String),
+}
+impl Identify for DateParseError {
+    fn get_identifier(&self) -> String {
+        match self {
+            DateParseError::Malformed(_) => "DateParseError::Malformed".to_string(),
+        }
+    }
+}
+impl Display for DateParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DateParseError::Malformed(value) => {
+                write!(f, "'{}' could not be parsed as the date. Write it like 2026-10-18, today or yesterday", value)
+            },
+        }
+    }
+}
+impl SpecificParseError for DateParseError {}
+
+pub struct DateConstraint;
+impl ValuefulConstraint for DateConstraint {
+    fn parse_value(&self, value: &str) -> Result<ArgumentValue, IdBox<dyn SpecificParseError>> {
+        parse_date(value, Local::now().date_naive())
+            .map(ArgumentValue::Date)
+            .ok_or_else(|| IdBox::new(Box::new(DateParseError::Malformed(value.to_owned())) as Box<dyn SpecificParseError>))
+    }
+
+    fn fill_placeholder(&self, value: &ArgumentValue, placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
+        let date = match value {
+            ArgumentValue::Date(d) => d,
+            _ => panic!("Unexpected ArgumentValue: {:#?}", value)
+        };
+
+        let format = placeholder_args.get("format").map(String::as_str).unwrap_or("%Y-%m-%d");
+
+        // The format including the time or the unknown specifier cannot be used to the date.
+        let items = StrftimeItems::new(format).collect::<Vec<_>>();
+        if items.iter().any(|i| matches!(i, Item::Error)) {
+            return Err(PlaceholderParseError::InvalidParameterValue { name: "format".to_string(), value: format.to_string() });
+        }
+
+        let mut filled = String::new();
+        std::fmt::write(&mut filled, format_args!("{}", date.format_with_items(items.into_iter())))
+            .map_err(|_| PlaceholderParseError::InvalidParameterValue { name: "format".to_string(), value: format.to_string() })?;
+
+        Ok(filled)
+    }
+}
+
+/// Parses the date like `2026-10-18`, `today`, `yesterday` or `tomorrow`, relative to `today`.
+fn parse_date(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match value.to_lowercase().as_str() {
+        "today" => Some(today),
+        "yesterday" => today.checked_sub_signed(Duration::days(1)),
+        "tomorrow" => today.checked_add_signed(Duration::days(1)),
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::HashMap;
+
+    use chrono::NaiveDate;
+    use rstest::rstest;
+
+    use crate::{map, constraints::{Constraint, ValueParseError}, domain::ArgumentValue, placeholder::PlaceholderParseError};
+
+    use super::{parse_date, DateConstraint};
+
+    #[rstest(input, expected,
+        case("2026-10-18", Some((2026, 10, 18))),
+        case("today", Some((2024, 3, 1))),
+        case("Yesterday", Some((2024, 2, 29))),
+        case("tomorrow", Some((2024, 3, 2))),
+        case("2026-02-30", None),
+        case("18/10/2026", None),
+    )]
+    fn parses_date(input: &str, expected: Option<(i32, u32, u32)>) {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+        let expected = expected.map(|(y, m, d)| NaiveDate::from_ymd_opt(y, m, d).unwrap());
+        assert_eq!(parse_date(input, today), expected)
+    }
+
+    #[test]
+    fn declines_malformed_date() {
+        let parsed = DateConstraint.parse_value(Some("someday"));
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), "DateParseError::Malformed")
+    }
+
+    #[rstest(properties, expected,
+        case(map!(), Ok("2026-10-08".to_string())),
+        case(map!("format" => "%Y%m%d"), Ok("20261008".to_string())),
+        case(map!("format" => "%d/%m/%y"), Ok("08/10/26".to_string())),
+        case(map!("format" => "%Q"), Err(PlaceholderParseError::InvalidParameterValue { name: "format".to_string(), value: "%Q".to_string() })),
+        case(map!("format" => "%H:%M"), Err(PlaceholderParseError::InvalidParameterValue { name: "format".to_string(), value: "%H:%M".to_string() })),
+    )]
+    fn fills_date_in_format(properties: HashMap<&str, &str>, expected: Result<String, PlaceholderParseError>) {
+        let properties = properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 8).unwrap();
+
+        assert_eq!(DateConstraint.fill_placeholder(&ArgumentValue::Date(date), &properties), expected)
+    }
+}
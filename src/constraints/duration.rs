@@ -0,0 +1,138 @@
+use std::{fmt::Display, collections::HashMap, time::Duration};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{domain::ArgumentValue, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
+
+use super::{ValuefulConstraint, SpecificParseError, number::format_number};
+
+static DURATION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+(\.\d+)?(ms|s|m|h|d))+$").unwrap());
+static PART_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+(?:\.\d+)?)(ms|s|m|h|d)").unwrap());
+
+#[derive(Debug)]
+pub enum DurationParseError {
+    Malformed(String),
+}
+impl Identify for DurationParseError {
+    fn get_identifier(&self) -> String {
+        match self {
+            DurationParseError::Malformed(_) => "DurationParseError::Malformed".to_string(),
+        }
+    }
+}
+impl Display for DurationParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DurationParseError::Malformed(value) => {
+                write!(f, "'{}' could not be parsed as the duration. Write it like 30s, 5m or 1h30m", value)
+            },
+        }
+    }
+}
+impl SpecificParseError for DurationParseError {}
+
+pub struct DurationConstraint;
+impl ValuefulConstraint for DurationConstraint {
+    fn parse_value(&self, value: &str) -> Result<ArgumentValue, IdBox<dyn SpecificParseError>> {
+        parse_duration(value)
+            .map(ArgumentValue::Duration)
+            .ok_or_else(|| IdBox::new(Box::new(DurationParseError::Malformed(value.to_owned())) as Box<dyn SpecificParseError>))
+    }
+
+    fn fill_placeholder(&self, value: &ArgumentValue, placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
+        let duration = match value {
+            ArgumentValue::Duration(d) => d,
+            _ => panic!("Unexpected ArgumentValue: {:#?}", value)
+        };
+
+        let unit = placeholder_args.get("unit").map(String::as_str).unwrap_or("s");
+        let unit_seconds = seconds_of(unit)
+            .ok_or_else(|| PlaceholderParseError::InvalidParameterValue { name: "unit".to_string(), value: unit.to_string() })?;
+
+        Ok(format_number(duration.as_secs_f64() / unit_seconds))
+    }
+}
+
+/// Parses the duration like `30s`, `1h30m` or `1.5h`. The number without the unit is treated as seconds.
+fn parse_duration(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+    if !DURATION_REGEX.is_match(value) {
+        return None;
+    }
+
+    let seconds = PART_REGEX.captures_iter(value)
+        .map(|cap| cap[1].parse::<f64>().ok().zip(seconds_of(&cap[2])).map(|(n, unit)| n * unit))
+        .sum::<Option<f64>>()?;
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+fn seconds_of(unit: &str) -> Option<f64> {
+    match unit {
+        "ms" => Some(0.001),
+        "s" => Some(1f64),
+        "m" => Some(60f64),
+        "h" => Some(3600f64),
+        "d" => Some(86400f64),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::{collections::HashMap, time::Duration};
+
+    use rstest::rstest;
+
+    use crate::{map, constraints::{Constraint, ValueParseError}, domain::ArgumentValue};
+
+    use super::DurationConstraint;
+
+    #[rstest(input, expected,
+        case("30s", 30f64),
+        case("5m", 300f64),
+        case("1h30m", 5400f64),
+        case("1.5h", 5400f64),
+        case("250ms", 0.25),
+        case("2d", 172800f64),
+        case("45", 45f64),
+    )]
+    fn accepts_duration(input: &str, expected: f64) {
+        let parsed = DurationConstraint.parse_value(Some(input));
+
+        assert_eq!(parsed, Ok(ArgumentValue::Duration(Duration::from_secs_f64(expected))))
+    }
+
+    #[rstest(input,
+        case("5 minutes"),
+        case("1h30"),
+        case("-5s"),
+        case(""),
+    )]
+    fn declines_malformed_duration(input: &str) {
+        let parsed = DurationConstraint.parse_value(Some(input));
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), "DurationParseError::Malformed")
+    }
+
+    #[rstest(properties, expected,
+        case(map!(), "5400"),
+        case(map!("unit" => "m"), "90"),
+        case(map!("unit" => "h"), "1.5"),
+        case(map!("unit" => "ms"), "5400000"),
+    )]
+    fn fills_duration_in_unit(properties: HashMap<&str, &str>, expected: &str) {
+        let properties = properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let filled = DurationConstraint.fill_placeholder(&ArgumentValue::Duration(Duration::from_secs(5400)), &properties);
+
+        assert_eq!(filled, Ok(expected.to_string()))
+    }
+}
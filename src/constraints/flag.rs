@@ -1,20 +1,57 @@
-use std::collections::HashMap;
+use std::{fmt::Display, collections::HashMap};
 
-use crate::{domain::ArgumentValue, placeholder::PlaceholderParseError};
+use crate::{domain::{ArgumentValue, FlagOptions}, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
 
-use super::{Constraint, ValueParseError};
+use super::{Constraint, SpecificParseError, ValueParseError};
 
-pub struct FlagConstraint;
+#[derive(Debug)]
+pub enum FlagParseError {
+    NotBoolean(String),
+    NotCount(String),
+}
+impl Identify for FlagParseError {
+    fn get_identifier(&self) -> String {
+        match self {
+            FlagParseError::NotBoolean(_) => "FlagParseError::NotBoolean".to_string(),
+            FlagParseError::NotCount(_) => "FlagParseError::NotCount".to_string(),
+        }
+    }
+}
+impl Display for FlagParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FlagParseError::NotBoolean(value) => write!(f, "'{}' is not the boolean. Use either of true, false, yes, no, 1 or 0", value),
+            FlagParseError::NotCount(value) => write!(f, "'{}' is not available as the count. Specify the number, or either of true or false", value),
+        }
+    }
+}
+impl SpecificParseError for FlagParseError {}
+
+#[derive(Default)]
+pub struct FlagConstraint {
+    options: FlagOptions
+}
 impl Constraint for FlagConstraint {
     fn parse_value(&self, value: Option<&str>) -> Result<ArgumentValue, ValueParseError> {
-        match value {
-            Some(_) => Err(ValueParseError::ValueUneccesary),
-            None => Ok(ArgumentValue::Flag(true))
+        let value = match value {
+            Some(v) => v,
+            None => return Ok(self.to_value(true))
+        };
+
+        match value.to_lowercase().as_str() {
+            "true" | "yes" => Ok(self.to_value(true)),
+            "false" | "no" => Ok(self.to_value(false)),
+            _ if self.options.count => value.parse::<u32>()
+                .map(|c| ArgumentValue::Number(c as f64))
+                .map_err(|_| ValueParseError::ParseFailed(IdBox::new(Box::new(FlagParseError::NotCount(value.to_owned()))))),
+            "1" => Ok(self.to_value(true)),
+            "0" => Ok(self.to_value(false)),
+            _ => Err(ValueParseError::ParseFailed(IdBox::new(Box::new(FlagParseError::NotBoolean(value.to_owned())))))
         }
     }
 
     fn fallback(&self) -> Result<ArgumentValue, ValueParseError> {
-        Ok(ArgumentValue::Flag(false))
+        Ok(self.to_value(self.options.default))
     }
 
     fn fill_placeholder(&self, value: &ArgumentValue, placeholder_args: &HashMap<String, String>) -> Result<String , PlaceholderParseError>{
@@ -39,35 +76,81 @@ impl Constraint for FlagConstraint {
         )
     }
 }
+impl FlagConstraint {
+    pub fn new(options: FlagOptions) -> Self {
+        Self { options }
+    }
 
+    /// The counted flag yields the count instead of the boolean.
+    fn to_value(&self, flag: bool) -> ArgumentValue {
+        if self.options.count {
+            ArgumentValue::Number(if flag { 1f64 } else { 0f64 })
+        } else {
+            ArgumentValue::Flag(flag)
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests{
-    use crate::{constraints::{Constraint, ValueParseError}, domain::ArgumentValue, helper::identify::Identify};
+    use rstest::rstest;
+
+    use crate::{constraints::{Constraint, ValueParseError}, domain::{ArgumentValue, FlagOptions}};
 
     use super::FlagConstraint;
 
     #[test]
     fn accept_and_raise_flag_when_value_is_not_supplied() {
-        let parsed = FlagConstraint.parse_value(None);
+        let parsed = FlagConstraint::default().parse_value(None);
 
         let parsed = parsed.expect("Should success, but failed");
         assert_eq!(parsed, ArgumentValue::Flag(true))
     }
 
+    #[rstest(input, expected,
+        case("true", true),
+        case("Yes", true),
+        case("1", true),
+        case("false", false),
+        case("no", false),
+        case("0", false),
+    )]
+    fn accept_boolean_value(input: &str, expected: bool) {
+        let parsed = FlagConstraint::default().parse_value(Some(input));
+
+        assert_eq!(parsed, Ok(ArgumentValue::Flag(expected)))
+    }
+
     #[test]
-    fn decline_any_value() {
-        let parsed = FlagConstraint.parse_value(Some("thing"));
+    fn decline_non_boolean_value() {
+        let parsed = FlagConstraint::default().parse_value(Some("thing"));
 
         let error = parsed.expect_err("Should fail, but succeeded");
-        assert_eq!(error, ValueParseError::ValueUneccesary)
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), "FlagParseError::NotBoolean")
     }
 
-    #[test]
-    fn return_false_value_when_argument_is_not_specified() {
-        let parsed = FlagConstraint.fallback();
+    #[rstest(default, case(false), case(true))]
+    fn return_default_value_when_argument_is_not_specified(default: bool) {
+        let parsed = FlagConstraint::new(FlagOptions { default, ..FlagOptions::default() }).fallback();
 
         let parsed = parsed.expect("Should success, but failed");
-        assert_eq!(parsed, ArgumentValue::Flag(false));
+        assert_eq!(parsed, ArgumentValue::Flag(default));
+    }
+
+    #[rstest(input, expected,
+        case(None, 1f64),
+        case(Some("3"), 3f64),
+        case(Some("false"), 0f64),
+        case(Some("true"), 1f64),
+    )]
+    fn yield_count_for_counted_flag(input: Option<&str>, expected: f64) {
+        let constraint = FlagConstraint::new(FlagOptions { count: true, ..FlagOptions::default() });
+
+        assert_eq!(constraint.parse_value(input), Ok(ArgumentValue::Number(expected)));
+        assert_eq!(constraint.fallback(), Ok(ArgumentValue::Number(0f64)));
     }
 }
@@ -0,0 +1,175 @@
+use std::{fmt::Display, collections::{BTreeMap, HashMap}};
+
+use crate::{domain::{ArgumentValue, MapOptions}, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
+
+use super::{Constraint, SpecificParseError, ValueParseError};
+
+#[derive(Debug)]
+pub enum MapParseError {
+    MissingSeparator(String),
+    UnknownKey { key: String, keys: Vec<String> },
+}
+impl Identify for MapParseError {
+    fn get_identifier(&self) -> String {
+        match self {
+            MapParseError::MissingSeparator(_) => "MapParseError::MissingSeparator".to_string(),
+            MapParseError::UnknownKey { .. } => "MapParseError::UnknownKey".to_string(),
+        }
+    }
+}
+impl Display for MapParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MapParseError::MissingSeparator(value) => write!(f, "'{}' should be the pair like 'key=value'", value),
+            MapParseError::UnknownKey { key, keys } => write!(f, "'{}' is not available as the key. Use either of: {}", key, keys.join(", ")),
+        }
+    }
+}
+impl SpecificParseError for MapParseError {}
+
+#[derive(Default)]
+pub struct MapConstraint {
+    options: MapOptions
+}
+impl Constraint for MapConstraint {
+    fn parse_value(&self, value: Option<&str>) -> Result<ArgumentValue, ValueParseError> {
+        let value = value.ok_or(ValueParseError::ValueRequired)?;
+
+        self.parse_pair(value)
+            .map(|(k, v)| ArgumentValue::Map(BTreeMap::from([(k, v)])))
+            .map_err(|e| ValueParseError::ParseFailed(IdBox::new(Box::new(e))))
+    }
+
+    fn fallback(&self) -> Result<ArgumentValue, ValueParseError> {
+        Ok(ArgumentValue::Map(BTreeMap::new()))
+    }
+
+    fn fill_placeholder(&self, value: &ArgumentValue, placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
+        let map = match value {
+            ArgumentValue::Map(m) => m,
+            _ => panic!("Unexpected ArgumentValue: {:#?}", value)
+        };
+
+        let template = placeholder_args.get("each").map(String::as_str).unwrap_or("%k=%v");
+        Ok(
+            map.iter()
+                .map(|(k, v)| fill_pair(template, k, v))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+    }
+}
+impl MapConstraint {
+    pub fn new(options: MapOptions) -> Self {
+        Self { options }
+    }
+
+    fn parse_pair(&self, value: &str) -> Result<(String, String), MapParseError> {
+        let (key, pair_value) = value.split_once('=')
+            .filter(|(k, _)| !k.is_empty())
+            .ok_or_else(|| MapParseError::MissingSeparator(value.to_owned()))?;
+
+        if !self.options.keys.is_empty() && !self.options.keys.iter().any(|k| k == key) {
+            return Err(MapParseError::UnknownKey { key: key.to_owned(), keys: self.options.keys.clone() });
+        }
+
+        Ok((key.to_owned(), pair_value.to_owned()))
+    }
+}
+
+/// Replaces `%k` and `%v` in the template at once, so that `%v` in the key is not replaced again.
+fn fill_pair(template: &str, key: &str, value: &str) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(i) = rest.find('%') {
+        filled.push_str(&rest[..i]);
+        rest = &rest[i..];
+        match rest.get(..2) {
+            Some("%k") => filled.push_str(key),
+            Some("%v") => filled.push_str(value),
+            _ => {
+                filled.push('%');
+                rest = &rest[1..];
+                continue;
+            }
+        }
+        rest = &rest[2..];
+    }
+    filled.push_str(rest);
+
+    filled
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::{BTreeMap, HashMap};
+
+    use rstest::rstest;
+
+    use crate::{map, constraints::{Constraint, ValueParseError}, domain::{ArgumentValue, MapOptions}, helper::identify::Identify};
+
+    use super::{MapConstraint, MapParseError};
+
+    #[rstest(input, key, value,
+        case("tag=latest", "tag", "latest"),
+        case("opts=a=b", "opts", "a=b"),
+        case("empty=", "empty", ""),
+    )]
+    fn accepts_pair(input: &str, key: &str, value: &str) {
+        let parsed = MapConstraint::default().parse_value(Some(input));
+
+        let expected = BTreeMap::from([(key.to_string(), value.to_string())]);
+        assert_eq!(parsed, Ok(ArgumentValue::Map(expected)))
+    }
+
+    #[rstest(input, expected,
+        case("tag", MapParseError::MissingSeparator("".to_string())),
+        case("=latest", MapParseError::MissingSeparator("".to_string())),
+        case("version=1", MapParseError::UnknownKey { key: "".to_string(), keys: vec![] }),
+    )]
+    fn declines_malformed_pair(input: &str, expected: MapParseError) {
+        let constraint = MapConstraint::new(MapOptions { keys: vec!["tag".to_string()] });
+        let parsed = constraint.parse_value(Some(input));
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), expected.get_identifier())
+    }
+
+    #[test]
+    fn fallbacks_to_empty_map() {
+        let parsed = MapConstraint::default().fallback();
+
+        assert_eq!(parsed, Ok(ArgumentValue::Map(BTreeMap::new())))
+    }
+
+    #[rstest(properties, expected,
+        case(map!(), "a=1 b=2"),
+        case(map!("each" => "--build-arg %k=%v"), "--build-arg a=1 --build-arg b=2"),
+        case(map!("each" => "--set %v"), "--set 1 --set 2"),
+    )]
+    fn fills_each_pair(properties: HashMap<&str, &str>, expected: &str) {
+        let value = ArgumentValue::Map(BTreeMap::from([
+            ("b".to_string(), "2".to_string()),
+            ("a".to_string(), "1".to_string()),
+        ]));
+        let properties = properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+
+        assert_eq!(MapConstraint::default().fill_placeholder(&value, &properties), Ok(expected.to_string()))
+    }
+
+    #[rstest(key, value, template, expected,
+        case("a%v", "1", "%k=%v", "a%v=1"),
+        case("a", "%k", "%v:%k", "%k:a"),
+        case("a", "1", "100% %k%%v", "100% a%1"),
+    )]
+    fn fills_pair_without_replacing_filled_text(key: &str, value: &str, template: &str, expected: &str) {
+        let value = ArgumentValue::Map(BTreeMap::from([(key.to_string(), value.to_string())]));
+        let properties = map!("each".to_string() => template.to_string());
+
+        assert_eq!(MapConstraint::default().fill_placeholder(&value, &properties), Ok(expected.to_string()))
+    }
+}
@@ -6,6 +6,11 @@ pub mod choice;
 pub mod number;
 pub mod text;
 pub mod flag;
+pub mod path;
+pub mod map;
+pub mod duration;
+pub mod size;
+pub mod date;
 
 pub trait SpecificParseError: Debug + Display + Identify {}
 
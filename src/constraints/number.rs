@@ -1,48 +1,182 @@
-use std::{fmt::Display, collections::HashMap};
+use std::{fmt::Display, collections::HashMap, str::FromStr};
 
-use crate::{domain::ArgumentValue, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
+use crate::{domain::{ArgumentValue, NumberOptions}, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
 
 use super::{ValuefulConstraint, SpecificParseError};
 
 #[derive(Debug)]
 pub enum NumberParseError {
-    NumberParseFailure(String)
+    NumberParseFailure(String),
+    OutOfRange { value: f64, min: Option<f64>, max: Option<f64> },
+    NotInteger(f64),
+    NotOnStep { value: f64, step: f64, base: f64 },
 }
 impl Identify for NumberParseError {
     fn get_identifier(&self) -> String {
-        "NumberParseError::NumberParseFailure".to_string()
+        match self {
+            NumberParseError::NumberParseFailure(_) => "NumberParseError::NumberParseFailure".to_string(),
+            NumberParseError::OutOfRange { .. } => "NumberParseError::OutOfRange".to_string(),
+            NumberParseError::NotInteger(_) => "NumberParseError::NotInteger".to_string(),
+            NumberParseError::NotOnStep { .. } => "NumberParseError::NotOnStep".to_string(),
+        }
     }
 }
 impl Display for NumberParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let NumberParseError::NumberParseFailure(orig) = self;
-        write!(f, "'{}' could not be parsed as the number (esp. f64)", orig)
+        match self {
+            NumberParseError::NumberParseFailure(orig) => {
+                write!(f, "'{}' could not be parsed as the number (esp. f64)", orig)
+            },
+            NumberParseError::OutOfRange { value, min, max } => match (min, max) {
+                (Some(min), Some(max)) => write!(f, "{} is out of the range. It should be between {} and {}", value, min, max),
+                (Some(min), None) => write!(f, "{} is out of the range. It should be {} or larger", value, min),
+                (None, Some(max)) => write!(f, "{} is out of the range. It should be {} or smaller", value, max),
+                (None, None) => write!(f, "{} is out of the range", value),
+            },
+            NumberParseError::NotInteger(value) => write!(f, "{} is not an integer", value),
+            NumberParseError::NotOnStep { value, step, base } => {
+                write!(f, "{} is not available. It should be {} plus a multiple of {}", value, base, step)
+            },
+        }
     }
 }
 impl SpecificParseError for NumberParseError {}
 
-pub struct NumberConstraint;
+/// The largest number of times `repeat` repeats the text.
+const MAX_REPEAT: usize = 1000;
+
+#[derive(Default)]
+pub struct NumberConstraint {
+    options: NumberOptions
+}
 impl ValuefulConstraint for NumberConstraint {
     fn parse_value(&self, value: &str) -> Result<ArgumentValue, IdBox<dyn SpecificParseError>> {
-        match value.parse::<f64>() {
-            Ok(v) => Ok(ArgumentValue::Number(v)),
-            Err(_) => Err(IdBox::new(Box::new(NumberParseError::NumberParseFailure(value.to_owned()))))
+        let number = match value.parse::<f64>() {
+            Ok(v) if v.is_finite() => v,
+            _ => return Err(IdBox::new(Box::new(NumberParseError::NumberParseFailure(value.to_owned()))))
+        };
+
+        if self.options.integer && number.fract() != 0f64 {
+            return Err(IdBox::new(Box::new(NumberParseError::NotInteger(number))));
+        }
+
+        let too_small = self.options.min.is_some_and(|min| number < min);
+        let too_large = self.options.max.is_some_and(|max| number > max);
+        if too_small || too_large {
+            return Err(IdBox::new(Box::new(NumberParseError::OutOfRange {
+                value: number,
+                min: self.options.min,
+                max: self.options.max
+            })));
         }
+
+        if let Some(step) = self.options.step {
+            let base = self.options.min.unwrap_or(0f64);
+            let steps = (number - base) / step;
+            if (steps - steps.round()).abs() > 1e-9 {
+                return Err(IdBox::new(Box::new(NumberParseError::NotOnStep { value: number, step, base })));
+            }
+        }
+
+        Ok(ArgumentValue::Number(number))
     }
 
-    fn fill_placeholder(&self, value: &ArgumentValue, _placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
-        match value {
-            ArgumentValue::Number(n) => Ok(n.to_string()),
+    fn fill_placeholder(&self, value: &ArgumentValue, placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
+        let mut number = match value {
+            ArgumentValue::Number(n) => *n,
             _ => panic!("Unexpected ArgumentValue: {:#?}", value)
+        };
+
+        if let Some(text) = placeholder_args.get("repeat") {
+            if number > MAX_REPEAT as f64 {
+                return Err(PlaceholderParseError::InvalidParameterValue { name: "repeat".to_string(), value: number.to_string() });
+            }
+
+            return Ok(vec![text.as_str(); number.max(0f64) as usize].join(" "));
+        }
+        if let Some(text) = fill_threshold_branch(number, placeholder_args)? {
+            return Ok(text);
+        }
+
+        if let Some(mul) = get_property::<f64>(placeholder_args, "mul")? {
+            number *= mul;
+        }
+        if let Some(add) = get_property::<f64>(placeholder_args, "add")? {
+            number += add;
         }
+
+        let sign = if number < 0f64 { "-" } else { "" };
+        let digits = if placeholder_args.contains_key("hex") {
+            if number.fract() != 0f64 {
+                return Err(PlaceholderParseError::InvalidParameterValue { name: "hex".to_string(), value: number.to_string() });
+            }
+
+            format!("{:x}", number.abs() as i64)
+        } else if let Some(precision) = get_property::<usize>(placeholder_args, "precision")? {
+            format!("{:.*}", precision, number.abs())
+        } else {
+            format_number(number.abs())
+        };
+
+        let width = get_property::<usize>(placeholder_args, "pad")?.unwrap_or(0);
+        Ok(format!("{}{:0>width$}", sign, digits, width = width.saturating_sub(sign.len())))
+    }
+}
+impl NumberConstraint {
+    pub fn new(options: NumberOptions) -> Self {
+        Self { options }
+    }
+}
+
+fn get_property<T: FromStr>(placeholder_args: &HashMap<String, String>, name: &str) -> Result<Option<T>, PlaceholderParseError> {
+    placeholder_args.get(name)
+        .map(|v| v.parse::<T>().map_err(|_| PlaceholderParseError::InvalidParameterValue {
+            name: name.to_string(),
+            value: v.to_string()
+        }))
+        .transpose()
+}
+
+/// Picks the branch like `gt1=--debug` with the largest threshold below the number.
+/// Returns `None` if no branch is given.
+fn fill_threshold_branch(number: f64, placeholder_args: &HashMap<String, String>) -> Result<Option<String>, PlaceholderParseError> {
+    let thresholds = placeholder_args.iter()
+        .filter_map(|(name, text)| name.strip_prefix("gt").map(|t| (t, text)))
+        .map(|(threshold, text)| match threshold.parse::<f64>() {
+            Ok(t) => Ok((t, text)),
+            Err(_) => Err(PlaceholderParseError::InvalidParameterValue { name: "gt".to_string(), value: threshold.to_string() })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if thresholds.is_empty() {
+        return Ok(None);
+    }
+
+    let branch = thresholds.into_iter()
+        .filter(|(threshold, _)| number > *threshold)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    Ok(Some(branch.map(|(_, text)| text.to_string()).unwrap_or_default()))
+}
+
+pub(super) fn format_number(number: f64) -> String {
+    // Integers are formatted through i64 so that they don't become like `-0`.
+    if number.fract() == 0f64 && number.abs() < i64::MAX as f64 {
+        (number as i64).to_string()
+    } else {
+        number.to_string()
     }
 }
 
 #[cfg(test)]
 mod tests{
+    use std::collections::HashMap;
+
     use rstest::rstest;
     use crate::{constraints::{Constraint, ValueParseError}, domain::ArgumentValue, helper::identify::Identify};
 
+    use crate::{map, domain::NumberOptions, placeholder::PlaceholderParseError};
+
     use super::{NumberConstraint, NumberParseError};
 
     #[rstest(input, expected,
@@ -52,7 +186,7 @@ mod tests{
         case("+123", 123f64),
     )]
     fn accepts_numeric_value(input: &str, expected: f64) {
-        let parsed = NumberConstraint.parse_value(Some(input));
+        let parsed = NumberConstraint::default().parse_value(Some(input));
 
         let parsed = parsed.expect("Should success, but failed");
         assert_eq!(parsed, ArgumentValue::Number(expected))
@@ -61,10 +195,13 @@ mod tests{
     #[rstest(input,
         case("Not numeric"),
         case("0xA"),
-        case("A")
+        case("A"),
+        case("NaN"),
+        case("inf"),
+        case("1e309"),
     )]
     fn declines_non_numeric_value(input: &str) {
-        let parsed = NumberConstraint.parse_value(Some(input));
+        let parsed = NumberConstraint::default().parse_value(Some(input));
 
         let error = parsed.expect_err("Should fail, but succeeded");
         let error = match error {
@@ -79,9 +216,83 @@ mod tests{
 
     #[rstest]
     fn fail_fallback() {
-        let parsed = NumberConstraint.fallback();
+        let parsed = NumberConstraint::default().fallback();
 
         let error = parsed.expect_err("Should fail, but succeeded");
         assert_eq!(error, ValueParseError::ValueRequired)
     }
+
+    #[rstest(input, expected,
+        case("0", NumberParseError::OutOfRange { value: 0f64, min: Some(1f64), max: Some(65535f64) }),
+        case("65536", NumberParseError::OutOfRange { value: 65536f64, min: Some(1f64), max: Some(65535f64) }),
+        case("80.5", NumberParseError::NotInteger(80.5)),
+        case("80", NumberParseError::NotOnStep { value: 80f64, step: 2f64, base: 1f64 }),
+    )]
+    fn declines_value_against_options(input: &str, expected: NumberParseError) {
+        let constraint = NumberConstraint::new(NumberOptions { min: Some(1f64), max: Some(65535f64), step: Some(2f64), integer: true });
+        let parsed = constraint.parse_value(Some(input));
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), expected.get_identifier());
+        assert_eq!(error.to_string(), expected.to_string())
+    }
+
+    #[test]
+    fn accepts_value_on_step() {
+        let constraint = NumberConstraint::new(NumberOptions { min: Some(0.5), step: Some(0.1), ..NumberOptions::default() });
+        let parsed = constraint.parse_value(Some("0.8"));
+
+        assert_eq!(parsed, Ok(ArgumentValue::Number(0.8)))
+    }
+
+    #[rstest(input, expected,
+        case(3f64, "3"),
+        case(-0f64, "0"),
+        case(1e16, "10000000000000000"),
+        case(0.25, "0.25"),
+    )]
+    fn fills_number_without_artifacts(input: f64, expected: &str) {
+        let filled = NumberConstraint::default().fill_placeholder(&ArgumentValue::Number(input), &HashMap::new());
+
+        assert_eq!(filled, Ok(expected.to_string()))
+    }
+
+    #[rstest(input, properties, expected,
+        case(1.5, map!("precision" => "2"), "1.50"),
+        case(7f64, map!("pad" => "4"), "0007"),
+        case(-7f64, map!("pad" => "4"), "-007"),
+        case(255f64, map!("hex" => ""), "ff"),
+        case(254f64, map!("hex" => "", "add" => "1", "pad" => "4"), "00ff"),
+        case(1.5, map!("mul" => "1000"), "1500"),
+        case(2f64, map!("mul" => "10", "add" => "1"), "21"),
+        case(3f64, map!("repeat" => "-v"), "-v -v -v"),
+        case(0f64, map!("repeat" => "-v"), ""),
+        case(2f64, map!("gt1" => "--debug"), "--debug"),
+        case(1f64, map!("gt1" => "--debug"), ""),
+        case(3f64, map!("gt0" => "--info", "gt2" => "--trace"), "--trace"),
+    )]
+    fn fills_number_with_properties(input: f64, properties: HashMap<&str, &str>, expected: &str) {
+        let properties = properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let filled = NumberConstraint::default().fill_placeholder(&ArgumentValue::Number(input), &properties);
+
+        assert_eq!(filled, Ok(expected.to_string()))
+    }
+
+    #[rstest(input, properties,
+        case(1.5, map!("hex" => "")),
+        case(1f64, map!("pad" => "four")),
+        case(1f64, map!("add" => "one")),
+        case(1f64, map!("gtone" => "--debug")),
+        case(4e9, map!("repeat" => "-v")),
+    )]
+    fn declines_invalid_properties(input: f64, properties: HashMap<&str, &str>) {
+        let properties = properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let filled = NumberConstraint::default().fill_placeholder(&ArgumentValue::Number(input), &properties);
+
+        assert!(matches!(filled, Err(PlaceholderParseError::InvalidParameterValue { .. })))
+    }
 }
@@ -0,0 +1,201 @@
+use std::{env, fmt::Display, collections::HashMap, fs, path::{Component, Path, PathBuf}};
+
+use crate::{domain::{ArgumentValue, PathKind, PathOptions}, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
+
+use super::{ValuefulConstraint, SpecificParseError};
+
+#[derive(Debug)]
+pub enum PathParseError {
+    NotExist(String),
+    NotFile(String),
+    NotDirectory(String),
+    UnexpectedExtension { value: String, extensions: Vec<String> },
+    Unresolvable(String),
+}
+impl Identify for PathParseError {
+    fn get_identifier(&self) -> String {
+        match self {
+            PathParseError::NotExist(_) => "PathParseError::NotExist".to_string(),
+            PathParseError::NotFile(_) => "PathParseError::NotFile".to_string(),
+            PathParseError::NotDirectory(_) => "PathParseError::NotDirectory".to_string(),
+            PathParseError::UnexpectedExtension { .. } => "PathParseError::UnexpectedExtension".to_string(),
+            PathParseError::Unresolvable(_) => "PathParseError::Unresolvable".to_string(),
+        }
+    }
+}
+impl Display for PathParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PathParseError::NotExist(path) => write!(f, "'{}' does not exist", path),
+            PathParseError::NotFile(path) => write!(f, "'{}' is not a file", path),
+            PathParseError::NotDirectory(path) => write!(f, "'{}' is not a directory", path),
+            PathParseError::UnexpectedExtension { value, extensions } => {
+                write!(f, "'{}' should have either of the extensions: {}", value, extensions.join(", "))
+            },
+            PathParseError::Unresolvable(path) => write!(f, "'{}' could not be resolved to the absolute path", path),
+        }
+    }
+}
+impl SpecificParseError for PathParseError {}
+
+pub struct PathConstraint {
+    options: PathOptions
+}
+impl ValuefulConstraint for PathConstraint {
+    fn parse_value(&self, value: &str) -> Result<ArgumentValue, IdBox<dyn SpecificParseError>> {
+        self.check(value).map_err(|e| IdBox::new(Box::new(e) as Box<dyn SpecificParseError>))
+    }
+
+    fn fill_placeholder(&self, value: &ArgumentValue, _placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
+        match value {
+            ArgumentValue::Text(t) => Ok(t.to_string()),
+            _ => panic!("Unexpected ArgumentValue: {:#?}", value)
+        }
+    }
+}
+impl PathConstraint {
+    pub fn new(options: PathOptions) -> Self {
+        Self { options }
+    }
+
+    fn check(&self, value: &str) -> Result<ArgumentValue, PathParseError> {
+        let path = Path::new(value);
+        let metadata = fs::metadata(path).ok();
+
+        match &metadata {
+            None if self.options.must_exist => return Err(PathParseError::NotExist(value.to_owned())),
+            Some(m) if self.options.kind == Some(PathKind::File) && !m.is_file() => {
+                return Err(PathParseError::NotFile(value.to_owned()))
+            },
+            Some(m) if self.options.kind == Some(PathKind::Dir) && !m.is_dir() => {
+                return Err(PathParseError::NotDirectory(value.to_owned()))
+            },
+            _ => {}
+        }
+
+        if !self.options.extensions.is_empty() {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+            if !self.options.extensions.iter().any(|e| e == extension) {
+                return Err(PathParseError::UnexpectedExtension {
+                    value: value.to_owned(),
+                    extensions: self.options.extensions.clone()
+                });
+            }
+        }
+
+        if !self.options.absolute {
+            return Ok(ArgumentValue::Text(value.to_owned()));
+        }
+
+        // The path which does not exist cannot be canonicalized, so it is joined to the current directory and normalized.
+        let absolute = match metadata {
+            Some(_) => fs::canonicalize(path).ok(),
+            None => env::current_dir().and_then(fs::canonicalize).ok().map(|d| normalize(&d.join(path)))
+        };
+
+        absolute
+            .and_then(|a| a.to_str().map(ToString::to_string))
+            .map(ArgumentValue::Text)
+            .ok_or_else(|| PathParseError::Unresolvable(value.to_owned()))
+    }
+}
+
+/// Removes `.` and `..` from the path without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => { normalized.pop(); },
+            c => normalized.push(c)
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests{
+    use std::env;
+
+    use rstest::rstest;
+
+    use crate::{constraints::{Constraint, ValueParseError}, domain::{ArgumentValue, PathKind, PathOptions}, helper::identify::Identify};
+
+    use super::{PathConstraint, PathParseError};
+
+    #[rstest(input, options,
+        case("not-existing.txt", PathOptions::default()),
+        case("Cargo.toml", PathOptions { must_exist: true, kind: Some(PathKind::File), ..PathOptions::default() }),
+        case("src", PathOptions { must_exist: true, kind: Some(PathKind::Dir), ..PathOptions::default() }),
+        case("Cargo.toml", PathOptions { extensions: vec!["yaml".to_string(), "toml".to_string()], ..PathOptions::default() }),
+    )]
+    fn accepts_path_matching_options(input: &str, options: PathOptions) {
+        let parsed = PathConstraint::new(options).parse_value(Some(input));
+
+        let parsed = parsed.expect("Should success, but failed");
+        assert_eq!(parsed, ArgumentValue::Text(input.to_string()))
+    }
+
+    #[rstest(input, options, expected,
+        case(
+            "not-existing.txt",
+            PathOptions { must_exist: true, ..PathOptions::default() },
+            PathParseError::NotExist("".to_string())
+        ),
+        case(
+            "src",
+            PathOptions { kind: Some(PathKind::File), ..PathOptions::default() },
+            PathParseError::NotFile("".to_string())
+        ),
+        case(
+            "Cargo.toml",
+            PathOptions { kind: Some(PathKind::Dir), ..PathOptions::default() },
+            PathParseError::NotDirectory("".to_string())
+        ),
+        case(
+            "Cargo.lock",
+            PathOptions { extensions: vec!["toml".to_string()], ..PathOptions::default() },
+            PathParseError::UnexpectedExtension { value: "".to_string(), extensions: vec![] }
+        ),
+    )]
+    fn declines_path_against_options(input: &str, options: PathOptions, expected: PathParseError) {
+        let parsed = PathConstraint::new(options).parse_value(Some(input));
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), expected.get_identifier())
+    }
+
+    #[rstest(input,
+        case("Cargo.toml"),
+        case("not-existing.txt"),
+    )]
+    fn resolves_absolute_path(input: &str) {
+        let options = PathOptions { absolute: true, ..PathOptions::default() };
+        let parsed = PathConstraint::new(options).parse_value(Some(input));
+
+        let expected = env::current_dir().unwrap().canonicalize().unwrap().join(input);
+        assert_eq!(parsed, Ok(ArgumentValue::Text(expected.to_str().unwrap().to_string())))
+    }
+
+    #[test]
+    fn normalizes_absolute_path_not_existing() {
+        let options = PathOptions { absolute: true, ..PathOptions::default() };
+        let parsed = PathConstraint::new(options).parse_value(Some("./src/../not-existing/../not-existing.txt"));
+
+        let expected = env::current_dir().unwrap().canonicalize().unwrap().join("not-existing.txt");
+        assert_eq!(parsed, Ok(ArgumentValue::Text(expected.to_str().unwrap().to_string())))
+    }
+
+    #[test]
+    fn fail_fallback() {
+        let parsed = PathConstraint::new(PathOptions::default()).fallback();
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        assert_eq!(error, ValueParseError::ValueRequired)
+    }
+}
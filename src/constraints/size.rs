@@ -0,0 +1,150 @@
+use std::{fmt::Display, collections::HashMap};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{domain::ArgumentValue, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
+
+use super::{ValuefulConstraint, SpecificParseError, number::format_number};
+
+static SIZE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+(?:\.\d+)?)\s*([A-Za-z]*)$").unwrap());
+
+#[derive(Debug)]
+pub enum SizeParseError {
+    Malformed(String),
+    UnknownUnit(String),
+    TooLarge(String),
+}
+impl Identify for SizeParseError {
+    fn get_identifier(&self) -> String {
+        match self {
+            SizeParseError::Malformed(_) => "SizeParseError::Malformed".to_string(),
+            SizeParseError::UnknownUnit(_) => "SizeParseError::UnknownUnit".to_string(),
+            SizeParseError::TooLarge(_) => "SizeParseError::TooLarge".to_string(),
+        }
+    }
+}
+impl Display for SizeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SizeParseError::Malformed(value) => write!(f, "'{}' could not be parsed as the size. Write it like 512M or 2GiB", value),
+            SizeParseError::UnknownUnit(unit) => write!(f, "'{}' is not available as the unit of the size", unit),
+            SizeParseError::TooLarge(value) => write!(f, "'{}' is too large as the size", value),
+        }
+    }
+}
+impl SpecificParseError for SizeParseError {}
+
+pub struct SizeConstraint;
+impl ValuefulConstraint for SizeConstraint {
+    fn parse_value(&self, value: &str) -> Result<ArgumentValue, IdBox<dyn SpecificParseError>> {
+        parse_size(value)
+            .map(ArgumentValue::Size)
+            .map_err(|e| IdBox::new(Box::new(e) as Box<dyn SpecificParseError>))
+    }
+
+    fn fill_placeholder(&self, value: &ArgumentValue, placeholder_args: &HashMap<String, String>) -> Result<String, PlaceholderParseError> {
+        let bytes = match value {
+            ArgumentValue::Size(b) => *b,
+            _ => panic!("Unexpected ArgumentValue: {:#?}", value)
+        };
+
+        let unit = placeholder_args.get("unit").map(String::as_str).unwrap_or("B");
+        let unit_bytes = bytes_of(unit)
+            .ok_or_else(|| PlaceholderParseError::InvalidParameterValue { name: "unit".to_string(), value: unit.to_string() })?;
+
+        Ok(format_number(bytes as f64 / unit_bytes as f64))
+    }
+}
+
+/// Parses the size like `512M` or `2GiB` into bytes. The number without the unit is treated as bytes.
+fn parse_size(value: &str) -> Result<u64, SizeParseError> {
+    let cap = SIZE_REGEX.captures(value.trim()).ok_or_else(|| SizeParseError::Malformed(value.to_owned()))?;
+
+    let number = cap[1].parse::<f64>().map_err(|_| SizeParseError::Malformed(value.to_owned()))?;
+    let unit = match &cap[2] {
+        "" => 1,
+        u => bytes_of(u).ok_or_else(|| SizeParseError::UnknownUnit(u.to_owned()))?
+    };
+
+    // `u64::MAX as f64` is rounded up to 2^64, which is already out of the range.
+    let bytes = (number * unit as f64).round();
+    if bytes >= u64::MAX as f64 {
+        return Err(SizeParseError::TooLarge(value.to_owned()));
+    }
+
+    Ok(bytes as u64)
+}
+
+/// Returns how many bytes the unit is. `K`, `M`, ... and `KiB`, `MiB`, ... are the powers of 1024, and `KB`, `MB`, ... are the powers of 1000.
+fn bytes_of(unit: &str) -> Option<u64> {
+    let unit = unit.to_uppercase();
+    if unit == "B" {
+        return Some(1);
+    }
+
+    let exponent = ["K", "M", "G", "T", "P"].iter().position(|p| unit.starts_with(p))? as u32 + 1;
+    match &unit[1..] {
+        "" | "IB" => Some(1024u64.pow(exponent)),
+        "B" => Some(1000u64.pow(exponent)),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use crate::{map, constraints::{Constraint, ValueParseError}, domain::ArgumentValue, helper::identify::Identify};
+
+    use super::{SizeConstraint, SizeParseError};
+
+    #[rstest(input, expected,
+        case("512M", 512 * 1024 * 1024),
+        case("2GiB", 2 * 1024 * 1024 * 1024),
+        case("10KB", 10_000),
+        case("1.5k", 1536),
+        case("100", 100),
+        case("64 MiB", 64 * 1024 * 1024),
+        case("16383P", 16383 * 1024u64.pow(5)),
+    )]
+    fn accepts_size(input: &str, expected: u64) {
+        let parsed = SizeConstraint.parse_value(Some(input));
+
+        assert_eq!(parsed, Ok(ArgumentValue::Size(expected)))
+    }
+
+    #[rstest(input, expected,
+        case("big", SizeParseError::Malformed("".to_string())),
+        case("-1M", SizeParseError::Malformed("".to_string())),
+        case("5X", SizeParseError::UnknownUnit("".to_string())),
+        case("5MiBs", SizeParseError::UnknownUnit("".to_string())),
+        case("16384P", SizeParseError::TooLarge("".to_string())),
+        case("99999999999999999999", SizeParseError::TooLarge("".to_string())),
+    )]
+    fn declines_malformed_size(input: &str, expected: SizeParseError) {
+        let parsed = SizeConstraint.parse_value(Some(input));
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), expected.get_identifier())
+    }
+
+    #[rstest(properties, expected,
+        case(map!(), "3145728"),
+        case(map!("unit" => "K"), "3072"),
+        case(map!("unit" => "MiB"), "3"),
+        case(map!("unit" => "MB"), "3.145728"),
+    )]
+    fn fills_size_in_unit(properties: HashMap<&str, &str>, expected: &str) {
+        let properties = properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let filled = SizeConstraint.fill_placeholder(&ArgumentValue::Size(3 * 1024 * 1024), &properties);
+
+        assert_eq!(filled, Ok(expected.to_string()))
+    }
+}
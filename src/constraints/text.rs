@@ -1,12 +1,67 @@
-use std::collections::HashMap;
+use std::{fmt::Display, collections::HashMap};
 
-use crate::{domain::ArgumentValue, helper::identify::IdBox, placeholder::PlaceholderParseError};
+use regex::Regex;
+
+use crate::{domain::{ArgumentValue, TextOptions}, helper::identify::{Identify, IdBox}, placeholder::PlaceholderParseError};
 
 use super::{ValuefulConstraint, SpecificParseError};
 
-pub struct TextConstraint;
+#[derive(Debug)]
+pub enum TextParseError {
+    PatternMismatch { value: String, pattern: String },
+    TooShort { value: String, min_len: usize },
+    TooLong { value: String, max_len: usize },
+    InvalidPattern(String),
+}
+impl Identify for TextParseError {
+    fn get_identifier(&self) -> String {
+        match self {
+            TextParseError::PatternMismatch { .. } => "TextParseError::PatternMismatch".to_string(),
+            TextParseError::TooShort { .. } => "TextParseError::TooShort".to_string(),
+            TextParseError::TooLong { .. } => "TextParseError::TooLong".to_string(),
+            TextParseError::InvalidPattern(_) => "TextParseError::InvalidPattern".to_string(),
+        }
+    }
+}
+impl Display for TextParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TextParseError::PatternMismatch { value, pattern } => write!(f, "'{}' does not match the pattern '{}'", value, pattern),
+            TextParseError::TooShort { value, min_len } => write!(f, "'{}' is too short. It should be {} character(s) or longer", value, min_len),
+            TextParseError::TooLong { value, max_len } => write!(f, "'{}' is too long. It should be {} character(s) or shorter", value, max_len),
+            TextParseError::InvalidPattern(pattern) => write!(f, "The pattern '{}' is invalid", pattern),
+        }
+    }
+}
+impl SpecificParseError for TextParseError {}
+
+#[derive(Default)]
+pub struct TextConstraint {
+    options: TextOptions
+}
 impl ValuefulConstraint for TextConstraint {
     fn parse_value(&self, value: &str) -> Result<ArgumentValue, IdBox<dyn SpecificParseError>> {
+        let length = value.chars().count();
+
+        if let Some(min_len) = self.options.min_len.filter(|m| length < *m) {
+            return Err(IdBox::new(Box::new(TextParseError::TooShort { value: value.to_owned(), min_len })));
+        }
+        if let Some(max_len) = self.options.max_len.filter(|m| length > *m) {
+            return Err(IdBox::new(Box::new(TextParseError::TooLong { value: value.to_owned(), max_len })));
+        }
+
+        if let Some(pattern) = &self.options.pattern {
+            let regex = Regex::new(pattern)
+                .map_err(|_| IdBox::new(Box::new(TextParseError::InvalidPattern(pattern.to_owned())) as Box<dyn SpecificParseError>))?;
+
+            if !regex.is_match(value) {
+                return Err(IdBox::new(Box::new(TextParseError::PatternMismatch {
+                    value: value.to_owned(),
+                    pattern: pattern.to_owned()
+                })));
+            }
+        }
+
         Ok(ArgumentValue::Text(value.to_owned()))
     }
 
@@ -17,24 +72,64 @@ impl ValuefulConstraint for TextConstraint {
         }
     }
 }
+impl TextConstraint {
+    pub fn new(options: TextOptions) -> Self {
+        Self { options }
+    }
+}
 
 #[cfg(test)]
 mod tests{
-    use crate::{constraints::{Constraint, ValueParseError}, domain::ArgumentValue, helper::identify::Identify};
+    use rstest::{fixture, rstest};
 
-    use super::TextConstraint;
+    use crate::{constraints::{Constraint, ValueParseError}, domain::{ArgumentValue, TextOptions}, helper::identify::Identify};
+
+    use super::{TextConstraint, TextParseError};
+
+    #[fixture]
+    pub fn constraint() -> TextConstraint {
+        TextConstraint::new(TextOptions {
+            pattern: Some("^[a-z0-9-]+$".to_string()),
+            min_len: Some(1),
+            max_len: Some(8),
+        })
+    }
 
     #[test]
     fn accpet_any_value() {
-        let parsed = TextConstraint.parse_value(Some("text"));
+        let parsed = TextConstraint::default().parse_value(Some("text"));
 
         let parsed = parsed.expect("Should success, but failed");
         assert_eq!(parsed, ArgumentValue::Text("text".to_string()))
     }
 
+    #[rstest]
+    fn accept_value_matching_options(constraint: TextConstraint) {
+        let parsed = constraint.parse_value(Some("feat-1"));
+
+        let parsed = parsed.expect("Should success, but failed");
+        assert_eq!(parsed, ArgumentValue::Text("feat-1".to_string()))
+    }
+
+    #[rstest(input, expected,
+        case("Feat", TextParseError::PatternMismatch { value: "".to_string(), pattern: "".to_string() }),
+        case("", TextParseError::TooShort { value: "".to_string(), min_len: 0 }),
+        case("feature-1", TextParseError::TooLong { value: "".to_string(), max_len: 0 }),
+    )]
+    fn decline_value_against_options(constraint: TextConstraint, input: &str, expected: TextParseError) {
+        let parsed = constraint.parse_value(Some(input));
+
+        let error = parsed.expect_err("Should fail, but succeeded");
+        let error = match error {
+            ValueParseError::ParseFailed(f) => f,
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        };
+        assert_eq!(error.get_identifier(), expected.get_identifier())
+    }
+
     #[test]
     fn fail_fallback() {
-        let parsed = TextConstraint.fallback();
+        let parsed = TextConstraint::default().fallback();
 
         let error = parsed.expect_err("Should fail, but succeeded");
         assert_eq!(error, ValueParseError::ValueRequired)
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::command::{parse, ParseError};
+use crate::domain::{Command, Config, Dependency, InputtedCommand};
+use crate::placeholder::{collect_arguments, fill_placeholder, PlaceholderParseError};
+
+#[derive(Debug, PartialEq)]
+pub enum DependencyError {
+    NotExist { command: String, dependency: String },
+    Cycle(Vec<String>),
+    MalformedArgument { dependency: String, error: ParseError },
+    MalformedPlaceholder { dependency: String, error: PlaceholderParseError },
+}
+impl Display for DependencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyError::NotExist { command, dependency } => {
+                write!(f, "'{}' needs '{}', but that command does not exist.", command, dependency)
+            },
+            DependencyError::Cycle(path) => {
+                write!(f, "The commands depend on each other: {}", path.join(" -> "))
+            },
+            DependencyError::MalformedArgument { dependency, error } => {
+                write!(f, "The arguments for '{}' is invalid: {}", dependency, error)
+            },
+            DependencyError::MalformedPlaceholder { dependency, error } => {
+                write!(f, "The placeholder in the arguments for '{}' is invalid: {}", dependency, error)
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Task {
+    pub command: InputtedCommand,
+    pub needs: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    InProgress,
+    Done
+}
+
+pub fn check_dependencies(config: &Config) -> Result<(), DependencyError> {
+    let mut visits = HashMap::<&str, Visit>::new();
+    for cmd in &config.command {
+        check_command(config, cmd, &mut visits, &mut Vec::new())?;
+    }
+
+    Ok(())
+}
+
+fn check_command<'a>(
+    config: &'a Config,
+    cmd: &'a Command,
+    visits: &mut HashMap<&'a str, Visit>,
+    path: &mut Vec<&'a str>
+) -> Result<(), DependencyError> {
+    path.push(&cmd.name);
+
+    match visits.get(cmd.name.as_str()) {
+        Some(Visit::Done) => {
+            path.pop();
+            return Ok(())
+        },
+        Some(Visit::InProgress) => {
+            let cycle_start = path.iter().position(|p| *p == cmd.name).unwrap_or(0);
+            return Err(DependencyError::Cycle(path[cycle_start..].iter().map(ToString::to_string).collect()));
+        },
+        None => {}
+    }
+
+    visits.insert(&cmd.name, Visit::InProgress);
+    for dep in &cmd.needs {
+        let dep_cmd = config.get_command(&dep.name).ok_or_else(|| DependencyError::NotExist {
+            command: cmd.name.clone(),
+            dependency: dep.name.clone()
+        })?;
+
+        check_arguments(config, cmd, dep)?;
+        check_command(config, dep_cmd, visits, path)?;
+    }
+    visits.insert(&cmd.name, Visit::Done);
+
+    path.pop();
+    Ok(())
+}
+
+fn check_arguments(config: &Config, cmd: &Command, dep: &Dependency) -> Result<(), DependencyError> {
+    let mut templated = false;
+    for arg in &dep.args {
+        let used = collect_arguments(arg).map_err(|error| DependencyError::MalformedPlaceholder {
+            dependency: dep.name.clone(),
+            error
+        })?;
+
+        if used.iter().any(|name| cmd.get_argument(name).is_none()) {
+            return Err(DependencyError::MalformedPlaceholder {
+                dependency: dep.name.clone(),
+                error: PlaceholderParseError::NotExistingArgument
+            });
+        }
+
+        templated |= !used.is_empty();
+    }
+
+    // The arguments filled with the values of the command can only be parsed when it is executed.
+    if templated {
+        return Ok(());
+    }
+
+    let line = [std::slice::from_ref(&dep.name), dep.args.as_slice()].concat();
+    parse(config, &line).map(|_| ()).map_err(|error| DependencyError::MalformedArgument {
+        dependency: dep.name.clone(),
+        error
+    })
+}
+
+pub fn resolve(config: &Config, root: InputtedCommand) -> Result<Vec<Task>, DependencyError> {
+    let mut tasks = Vec::new();
+    resolve_command(config, root, &mut tasks)?;
+
+    Ok(tasks)
+}
+
+fn resolve_command(config: &Config, cmd: InputtedCommand, tasks: &mut Vec<Task>) -> Result<usize, DependencyError> {
+    // SAFETY: InputtedCommand is only made from the command in the config.
+    let definition = config.get_command(&cmd.name).unwrap();
+
+    let mut needs = Vec::new();
+    for dep in &definition.needs {
+        let args = dep.args.iter()
+            .map(|a| fill_placeholder(a, &cmd.args))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| DependencyError::MalformedPlaceholder { dependency: dep.name.clone(), error })?;
+
+        let line = [vec![dep.name.clone()], args].concat();
+        let dep_cmd = parse(config, &line).map_err(|error| DependencyError::MalformedArgument {
+            dependency: dep.name.clone(),
+            error
+        })?;
+
+        let index = resolve_command(config, dep_cmd, tasks)?;
+        if !needs.contains(&index) {
+            needs.push(index);
+        }
+    }
+
+    if let Some(index) = tasks.iter().position(|t| t.command == cmd) {
+        return Ok(index);
+    }
+
+    tasks.push(Task { command: cmd, needs });
+    Ok(tasks.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use crate::{map, config::DeserializedConfig, domain::{Config, ArgumentValue, InputtedCommand}};
+    use crate::{command::ParseError, placeholder::PlaceholderParseError};
+
+    use super::{check_dependencies, resolve, DependencyError};
+
+    fn load(yaml: &str) -> Config {
+        serde_yaml::from_str::<DeserializedConfig>(yaml).unwrap().try_into().unwrap()
+    }
+
+    fn cmd(name: &str, args: HashMap<String, ArgumentValue>) -> InputtedCommand {
+        InputtedCommand { name: name.to_string(), args }
+    }
+
+    #[test]
+    fn runs_each_dependency_once_in_order() {
+        let config = load(include_str!("../tests/dependency_config.yaml"));
+        check_dependencies(&config).expect("Should success, but failed");
+
+        let tasks = resolve(&config, cmd("all", map!())).expect("Should success, but failed");
+        let order: Vec<_> = tasks.iter().map(|t| t.command.name.as_str()).collect();
+        assert_eq!(order, vec!["lint", "build", "test", "build", "all"]);
+
+        assert_eq!(tasks[1].command.args, map!("release".to_string() => ArgumentValue::Flag(false)));
+        assert_eq!(tasks[3].command.args, map!("release".to_string() => ArgumentValue::Flag(true)));
+        assert_eq!(tasks[2].needs, vec![0, 1]);
+        assert_eq!(tasks[4].needs, vec![0, 2, 3]);
+    }
+
+    #[rstest(release,
+        case(true),
+        case(false),
+    )]
+    fn fills_dependency_arguments_with_values_of_command(release: bool) {
+        let config = load(r#"
+            cmd:
+              build: { args: { release: { flag: } } }
+              ship:
+                args: { release: { flag: } }
+                needs: [{ cmd: build, args: { release: "{release|true=true|false=false}" } }]
+        "#);
+        check_dependencies(&config).expect("Should success, but failed");
+
+        let tasks = resolve(&config, cmd("ship", map!("release".to_string() => ArgumentValue::Flag(release))))
+            .expect("Should success, but failed");
+
+        assert_eq!(tasks[0].command, cmd("build", map!("release".to_string() => ArgumentValue::Flag(release))));
+    }
+
+    #[rstest(yaml, expected,
+        case(
+            "cmd: { a: { needs: [{ cmd: b, args: { nope: true } }] }, b: {} }",
+            DependencyError::MalformedArgument { dependency: "b".to_string(), error: ParseError::ArgumentNotExist }
+        ),
+        case(
+            "cmd: { a: { needs: [{ cmd: b, args: { n: \"{m}\" } }] }, b: { args: { n: { text: } } } }",
+            DependencyError::MalformedPlaceholder { dependency: "b".to_string(), error: PlaceholderParseError::NotExistingArgument }
+        ),
+    )]
+    fn declines_malformed_dependency_arguments(yaml: &str, expected: DependencyError) {
+        let error = check_dependencies(&load(yaml)).expect_err("Should fail, but succeeded");
+
+        assert_eq!(error, expected);
+    }
+
+    #[rstest(yaml, expected,
+        case(
+            "cmd: { a: { needs: [b] }, b: { needs: [a] } }",
+            vec!["a", "b", "a"]
+        ),
+        case(
+            "cmd: { a: { needs: [a] } }",
+            vec!["a", "a"]
+        ),
+    )]
+    fn detects_cycle(yaml: &str, expected: Vec<&str>) {
+        let error = check_dependencies(&load(yaml)).expect_err("Should fail, but succeeded");
+
+        match error {
+            DependencyError::Cycle(path) => {
+                assert_eq!(path.len(), expected.len());
+                assert_eq!(path.first(), path.last());
+            },
+            _ => panic!("Unexpected error yielded: {:#?}", error)
+        }
+    }
+
+    #[test]
+    fn declines_not_existing_dependency() {
+        let error = check_dependencies(&load("cmd: { a: { needs: [b] } }")).expect_err("Should fail, but succeeded");
+
+        assert_eq!(error, DependencyError::NotExist { command: "a".to_string(), dependency: "b".to_string() });
+    }
+}
@@ -1,9 +1,32 @@
 use crate::domain::Constraints;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct Argument {
     pub name: String,
     pub short_hand: Option<String>,
     pub constraint: Constraints,
     pub multi: bool,
+    pub secret: bool,
+    /// The environment variable which gives the value if the argument is not given.
+    pub env: Option<String>,
+    pub relations: ArgumentRelations,
+}
+impl Argument {
+    /// Returns the value of the environment variable of the argument. The empty value is regarded as not set.
+    pub fn env_value(&self) -> Option<String> {
+        self.env.as_ref()
+            .and_then(|e| std::env::var(e).ok())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// How the argument relates to the other arguments of the command.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ArgumentRelations {
+    /// The arguments which cannot be given together with this.
+    pub conflicts_with: Vec<String>,
+    /// The arguments which must be given together with this.
+    pub requires: Vec<String>,
+    /// This must be given if any of the arguments has the value, like `env: prod`.
+    pub required_if: Vec<(String, String)>,
 }
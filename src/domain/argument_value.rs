@@ -1,6 +1,17 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum ArgumentValue {
     Text(String),
     Flag(bool),
     Number(f64),
+    Choice { key: String, value: String },
+    Map(BTreeMap<String, String>),
+    Duration(Duration),
+    /// The size in bytes.
+    Size(u64),
+    Date(NaiveDate),
 }
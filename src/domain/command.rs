@@ -1,13 +1,57 @@
 use super::argument::Argument;
+use super::dependency::Dependency;
+use super::step::Step;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct Command {
     pub name: String,
     pub args: Vec<Argument>,
-    pub run: String
+    pub needs: Vec<Dependency>,
+    pub run: Vec<Step>,
+    pub finally: Vec<Step>,
+    pub groups: Vec<ArgumentGroup>,
+    pub presets: Vec<Preset>,
+    /// The question which should be answered yes before running, like `Deploy to {env}?`.
+    pub confirm: Option<String>
 }
 impl Command {
     pub fn get_argument(&self, name: &str) -> Option<&Argument> {
         self.args.iter().find(|c| c.name == name)
     }
+
+    pub fn get_preset(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    pub fn get_argument_by_short_hand(&self, short_hand: &str) -> Option<&Argument> {
+        self.args.iter().find(|c| c.short_hand.as_deref() == Some(short_hand))
+    }
+
+    /// Finds the argument specified by the option like `--name` or `-n`.
+    pub fn get_argument_by_option(&self, option: &str) -> Option<&Argument> {
+        match option.strip_prefix("--") {
+            Some(name) => self.get_argument(name),
+            None => option.strip_prefix('-').and_then(|s| self.get_argument_by_short_hand(s))
+        }
+    }
+}
+
+/// The arguments of which at most one can be given.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArgumentGroup {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+/// The named set of the argument values, which is given like `@name`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    pub args: Vec<(String, String)>,
+}
+impl Preset {
+    /// Returns the arguments like `--name=value`.
+    pub fn to_words(&self) -> Vec<String> {
+        self.args.iter().map(|(name, value)| format!("--{}={}", name, value)).collect()
+    }
 }
@@ -1,8 +1,11 @@
 use super::command::Command;
+use super::step::Step;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct Config {
-    pub command: Vec<Command>
+    pub command: Vec<Command>,
+    pub before: Vec<Step>,
+    pub after: Vec<Step>
 }
 
 impl Config {
@@ -1,7 +1,93 @@
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub enum Constraints {
-    Text,
-    Flag,
-    Number,
-    Choice(Vec<String>),
+    Text(TextOptions),
+    Flag(FlagOptions),
+    Number(NumberOptions),
+    Choice(ChoiceOptions),
+    Path(PathOptions),
+    Map(MapOptions),
+    Duration,
+    Size,
+    Date,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ChoiceOptions {
+    pub source: ChoiceSource,
+    pub matching: ChoiceMatch,
+    pub case_sensitive: bool,
+}
+impl From<ChoiceSource> for ChoiceOptions {
+    fn from(source: ChoiceSource) -> Self {
+        ChoiceOptions { source, matching: ChoiceMatch::default(), case_sensitive: true }
+    }
+}
+
+/// How the typed value is matched against the choices. The choice typed exactly is always accepted.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ChoiceMatch {
+    Exact,
+    #[default]
+    Prefix,
+    Fuzzy,
+}
+
+/// Where the choices come from. The choices except `Static` are computed when they are used.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum ChoiceSource {
+    Static(Vec<Choice>),
+    Command(String),
+    Glob { pattern: String, strip_ext: bool },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Choice {
+    pub key: String,
+    pub value: Option<String>,
+    pub help: Option<String>,
+}
+impl From<String> for Choice {
+    fn from(key: String) -> Self {
+        Choice { key, value: None, help: None }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FlagOptions {
+    pub default: bool,
+    pub count: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NumberOptions {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub step: Option<f64>,
+    pub integer: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TextOptions {
+    pub pattern: Option<String>,
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct PathOptions {
+    pub must_exist: bool,
+    pub kind: Option<PathKind>,
+    pub extensions: Vec<String>,
+    pub absolute: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MapOptions {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PathKind {
+    File,
+    Dir,
 }
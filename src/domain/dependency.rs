@@ -0,0 +1,5 @@
+#[derive(Debug, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub args: Vec<String>
+}
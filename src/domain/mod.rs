@@ -3,11 +3,15 @@ pub mod argument_value;
 pub mod command;
 pub mod config;
 pub mod constraint;
+pub mod dependency;
 pub mod input_command;
+pub mod step;
 
 pub use argument::*;
 pub use argument_value::*;
 pub use command::*;
 pub use config::*;
 pub use constraint::*;
+pub use dependency::*;
 pub use input_command::*;
+pub use step::*;
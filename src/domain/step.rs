@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Step {
+    pub name: Option<String>,
+    pub run: String,
+    pub shell: Option<String>,
+    pub cwd: Option<String>,
+    pub env: HashMap<String, String>,
+    pub condition: Option<String>,
+    pub continue_on_error: bool,
+}
+impl Step {
+    pub fn new(run: String) -> Self {
+        Self {
+            name: None,
+            run,
+            shell: None,
+            cwd: None,
+            env: HashMap::new(),
+            condition: None,
+            continue_on_error: false
+        }
+    }
+}
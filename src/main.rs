@@ -1,6 +1,8 @@
+mod condition;
 mod config;
 mod constraints;
 mod command;
+mod dependency;
 mod domain;
 mod helper;
 mod macros;
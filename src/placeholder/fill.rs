@@ -1,31 +1,40 @@
 use std::collections::HashMap;
 use std::ops::Range;
 
-use crate::{domain::ArgumentValue, constraints::{text::TextConstraint, Constraint, number::NumberConstraint, flag::FlagConstraint}};
+use crate::{domain::ArgumentValue, constraints::{text::TextConstraint, Constraint, number::NumberConstraint, flag::FlagConstraint, choice::ChoiceConstraint, map::MapConstraint, duration::DurationConstraint, size::SizeConstraint, date::DateConstraint}};
 
 use super::{Placeholder, PlaceholderParseError};
 
-pub(super) fn fill_first_placeholder(original: &str, values: &HashMap<String, ArgumentValue>, placeholder: &Placeholder) -> Result<(String, Range<usize>), PlaceholderParseError> {
-    let original = original.to_owned();
+pub(super) fn fill_value(values: &HashMap<String, ArgumentValue>, placeholder: &Placeholder) -> Result<String, PlaceholderParseError> {
     let value = values.get(&placeholder.arg_name).ok_or(PlaceholderParseError::NotExistingArgument)?;
 
-    let filling_value = match value {
-        ArgumentValue::Text(_) => TextConstraint.fill_placeholder(value, &placeholder.args),
-        ArgumentValue::Number(_) => NumberConstraint.fill_placeholder(value, &placeholder.args),
-        ArgumentValue::Flag(_) => FlagConstraint.fill_placeholder(value, &placeholder.args),
-    }?;
+    match value {
+        ArgumentValue::Text(_) => TextConstraint::default().fill_placeholder(value, &placeholder.args),
+        ArgumentValue::Number(_) => NumberConstraint::default().fill_placeholder(value, &placeholder.args),
+        ArgumentValue::Flag(_) => FlagConstraint::default().fill_placeholder(value, &placeholder.args),
+        ArgumentValue::Choice { .. } => ChoiceConstraint::new(Vec::new()).fill_placeholder(value, &placeholder.args),
+        ArgumentValue::Map(_) => MapConstraint::default().fill_placeholder(value, &placeholder.args),
+        ArgumentValue::Duration(_) => DurationConstraint.fill_placeholder(value, &placeholder.args),
+        ArgumentValue::Size(_) => SizeConstraint.fill_placeholder(value, &placeholder.args),
+        ArgumentValue::Date(_) => DateConstraint.fill_placeholder(value, &placeholder.args),
+    }
+}
+
+pub(super) fn fill_first_placeholder(original: &str, filling_value: &str, placeholder: &Placeholder) -> Result<(String, Range<usize>), PlaceholderParseError> {
+    let original = original.to_owned();
 
     let mut bytes = original
         .as_bytes()
         .to_vec();
 
+    let filled = format!("{}{}", placeholder.prefix, filling_value);
     bytes.splice(
         placeholder.range.clone(),
-        format!("{}{}", placeholder.prefix, filling_value).as_bytes().to_vec()
+        filled.as_bytes().to_vec()
     );
 
     match String::from_utf8(bytes) {
-        Ok(t) => Ok((t, Range { start: placeholder.range.start, end: placeholder.range.start + filling_value.len() } )),
+        Ok(t) => Ok((t, Range { start: placeholder.range.start, end: placeholder.range.start + filled.len() } )),
         Err(_) => Err(PlaceholderParseError::CorruptedDuringFill)
     }
 }
@@ -38,7 +47,7 @@ mod tests {
 
     use crate::{map, domain::ArgumentValue};
 
-    use super::{fill_first_placeholder, Placeholder};
+    use super::{fill_first_placeholder, fill_value, Placeholder};
 
     #[rstest(placeholder, expected, value, placeholder_arg,
         case("1234____9012", "123456789012", ArgumentValue::Text("5678".to_string()), None),
@@ -56,12 +65,15 @@ mod tests {
 
         let blanket_start = placeholder.find('_').unwrap();
 
-        let filled = fill_first_placeholder(placeholder, &value_map, &Placeholder {
+        let target = Placeholder {
             range: blanket_start..(blanket_start + 4),
             arg_name: "fill".to_string(),
             prefix: "".to_string(),
             args: placeholder_arg.unwrap_or_default().iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
-        });
+        };
+
+        let filling_value = fill_value(&value_map, &target).expect("Should success, but failed");
+        let filled = fill_first_placeholder(placeholder, &filling_value, &target);
         assert_eq!(filled.map(|(s, _)| s), Ok(expected.to_string()))
     }
 }
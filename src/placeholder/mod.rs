@@ -9,6 +9,7 @@ pub enum PlaceholderParseError {
     NotExistingArgument,
     MalformedParameter,
     InsufficientParameter(String),
+    InvalidParameterValue { name: String, value: String },
     CorruptedDuringFill
 }
 impl Display for PlaceholderParseError {
@@ -17,6 +18,7 @@ impl Display for PlaceholderParseError {
             PlaceholderParseError::NotExistingArgument => write!(f, "Not existing argument is used in the placeholder."),
             PlaceholderParseError::MalformedParameter => write!(f, "The arguments in the placeholder is something wrong."),
             PlaceholderParseError::InsufficientParameter(_) => write!(f, "Required argument(s) is not specified in the placeholder."),
+            PlaceholderParseError::InvalidParameterValue { name, value } => write!(f, "'{}' is not available for the property '{}' of the placeholder.", value, name),
             PlaceholderParseError::CorruptedDuringFill => write!(f, "The text has been corrupted during fill. This is internal bug!"),
         }
     }
@@ -31,16 +33,61 @@ pub struct Placeholder {
 }
 
 pub fn fill_placeholder(line: &str, values: &HashMap<String, ArgumentValue>) -> Result<String, PlaceholderParseError> {
+    fill_placeholder_with(line, values, |_, filled| filled)
+}
+
+/// Fills the placeholders of the secret arguments with the reference to the environment variable instead of the value,
+/// so that the value is not written into the script. The environment variables to pass to the script are returned together.
+/// `reference` makes the reference from the name of the variable and whether the placeholder is in the double quotes,
+/// which depends on the shell running the script.
+pub fn fill_placeholder_hiding(
+    line: &str,
+    values: &HashMap<String, ArgumentValue>,
+    secrets: &[String],
+    reference: impl Fn(&str, bool) -> String
+) -> Result<(String, HashMap<String, String>), PlaceholderParseError> {
+    // The placeholders are filled in the same order as they are listed.
+    let mut quotes = quotes_of_placeholders(line)?.into_iter().map(|(_, q)| q);
+    let mut env = HashMap::new();
+    let line = fill_placeholder_with(line, values, |name, filled| {
+        let quote = quotes.next();
+        if !secrets.iter().any(|s| s == name) {
+            return filled;
+        }
+
+        let variable = format!("RXE_SECRET_{}", env.len() + 1);
+        let filling = reference(&variable, quote == Some(Quote::Double));
+        env.insert(variable, filled);
+
+        filling
+    })?;
+
+    Ok((line, env))
+}
+
+/// Fills the placeholders like `fill_placeholder`, but the values of the secret arguments are masked to be shown.
+pub fn fill_placeholder_masking(line: &str, values: &HashMap<String, ArgumentValue>, secrets: &[String]) -> Result<String, PlaceholderParseError> {
+    fill_placeholder_with(line, values, |name, filled| {
+        if secrets.iter().any(|s| s == name) { "****".to_string() } else { filled }
+    })
+}
+
+fn fill_placeholder_with(
+    line: &str,
+    values: &HashMap<String, ArgumentValue>,
+    mut convert: impl FnMut(&str, String) -> String
+) -> Result<String, PlaceholderParseError> {
     let mut line = line.to_owned();
     let mut previous_index = 0;
     while let Some(p) = parse::parse_first_placeholder(&line, previous_index)? {
-        let (new_line, range) = fill::fill_first_placeholder(&line, values, &p)?;
+        let filling_value = convert(&p.arg_name, fill::fill_value(values, &p)?);
+        let (new_line, range) = fill::fill_first_placeholder(&line, &filling_value, &p)?;
         line = new_line;
-        previous_index = range.end + 1;
+        previous_index = range.end;
 
         if p.range.start > 2 {
             let double_slash_range = Range { start: p.range.start, end: p.range.start + 2 };
-            if &line[double_slash_range.clone()] == r"\\" {
+            if line.get(double_slash_range.clone()) == Some(r"\\") {
                 line.replace_range(double_slash_range, r"\");
                 previous_index -= 1;
             }
@@ -52,14 +99,78 @@ pub fn fill_placeholder(line: &str, values: &HashMap<String, ArgumentValue>) ->
     Ok(line)
 }
 
+/// Lists the names of the properties given to the placeholders of the argument in the line.
+pub fn collect_properties(line: &str, arg_name: &str) -> Result<Vec<String>, PlaceholderParseError> {
+    let mut properties = Vec::new();
+    let mut previous_index = 0;
+    while let Some(p) = parse::parse_first_placeholder(line, previous_index)? {
+        previous_index = p.range.end;
+        if p.arg_name == arg_name {
+            properties.extend(p.args.into_keys());
+        }
+    }
+
+    Ok(properties)
+}
+
+/// Lists the names of the arguments used in the placeholders in the line.
+pub fn collect_arguments(line: &str) -> Result<Vec<String>, PlaceholderParseError> {
+    let mut arguments = Vec::new();
+    let mut previous_index = 0;
+    while let Some(p) = parse::parse_first_placeholder(line, previous_index)? {
+        previous_index = p.range.end;
+        arguments.push(p.arg_name);
+    }
+
+    Ok(arguments)
+}
+
+/// Lists the names of the arguments whose placeholders are in the single quotes, where no variable is expanded by the shell.
+pub fn collect_single_quoted_arguments(line: &str) -> Result<Vec<String>, PlaceholderParseError> {
+    let placeholders = quotes_of_placeholders(line)?;
+
+    Ok(placeholders.into_iter().filter(|(_, q)| *q == Quote::Single).map(|(name, _)| name).collect())
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Quote {
+    Bare,
+    Single,
+    Double,
+}
+
+/// Lists the names of the arguments of the placeholders in the line, with the quotes of the shell they are in.
+fn quotes_of_placeholders(line: &str) -> Result<Vec<(String, Quote)>, PlaceholderParseError> {
+    let mut placeholders = Vec::new();
+    let (mut quote, mut escaped) = (Quote::Bare, false);
+    let mut previous_index = 0;
+    while let Some(p) = parse::parse_first_placeholder(line, previous_index)? {
+        // The range of the placeholder starts with the prefix, which is the character before the brace.
+        for c in line[previous_index..p.range.start + p.prefix.len()].chars() {
+            quote = match (quote, c) {
+                _ if escaped => { escaped = false; quote },
+                (Quote::Bare | Quote::Double, '\\') => { escaped = true; quote },
+                (Quote::Bare, '\'') => Quote::Single,
+                (Quote::Single, '\'') => Quote::Bare,
+                (Quote::Bare, '"') => Quote::Double,
+                (Quote::Double, '"') => Quote::Bare,
+                _ => quote
+            };
+        }
+        previous_index = p.range.end;
+        placeholders.push((p.arg_name, quote));
+    }
+
+    Ok(placeholders)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
     use rstest::rstest;
 
     use crate::{map, domain::ArgumentValue};
-    use crate::placeholder::fill::fill_first_placeholder;
-    use crate::placeholder::fill_placeholder;
+    use crate::placeholder::{collect_properties, collect_single_quoted_arguments, fill_placeholder, fill_placeholder_hiding, fill_placeholder_masking};
 
     #[rstest(original, expected, value,
         case(
@@ -70,6 +181,20 @@ mod tests {
                 "target" => ArgumentValue::Text("world".to_string())
             )
         ),
+        case(
+            "{fill}{fill}",
+            "FILLEDFILLED",
+            map!(
+                "fill" => ArgumentValue::Text("FILLED".to_string()),
+            )
+        ),
+        case(
+            "echo {release|true=--release}",
+            "echo ",
+            map!(
+                "release" => ArgumentValue::Flag(false),
+            )
+        ),
         case(
             r"\{fill} {fill} \{fill} {fill}",
             r"\{fill} FILLED \{fill} FILLED",
@@ -100,4 +225,52 @@ mod tests {
             Ok(expected.to_string())
         )
     }
+
+    #[test]
+    fn collects_properties_of_argument() {
+        let mut properties = collect_properties("{env|prod=a} {tag} {env|key} {env|else=b}", "env").unwrap();
+        properties.sort();
+
+        assert_eq!(properties, vec!["else", "key", "prod"]);
+    }
+
+    #[test]
+    fn hides_secret_values_behind_environment_variables() {
+        let values = map!(
+            "token".to_string() => ArgumentValue::Text("s3cr3t".to_string()),
+            "user".to_string() => ArgumentValue::Text("alice".to_string()),
+        );
+
+        let reference = |v: &str, quoted: bool| if quoted { format!("<{}>", v) } else { v.to_string() };
+        let (line, env) = fill_placeholder_hiding(r#"login {user} {token} "as {user}: {token}""#, &values, &["token".to_string()], reference).unwrap();
+
+        assert_eq!(line, r#"login alice RXE_SECRET_1 "as alice: <RXE_SECRET_2>""#);
+        assert_eq!(env, map!(
+            "RXE_SECRET_1".to_string() => "s3cr3t".to_string(),
+            "RXE_SECRET_2".to_string() => "s3cr3t".to_string(),
+        ));
+    }
+
+    #[rstest(line, expected,
+        case("login {user} {token}", vec![]),
+        case("login '{user}' {token}", vec!["user"]),
+        case("login \"'{user}'\" '\\' {token}", vec![]),
+        case("login \\'{user} 'a'{token}'b'", vec![]),
+        case("login 'as {user}' and '{token}", vec!["user", "token"]),
+    )]
+    fn collects_single_quoted_arguments(line: &str, expected: Vec<&str>) {
+        assert_eq!(collect_single_quoted_arguments(line), Ok(expected.iter().map(ToString::to_string).collect()));
+    }
+
+    #[test]
+    fn masks_secret_values() {
+        let values = map!(
+            "token".to_string() => ArgumentValue::Text("s3cr3t".to_string()),
+            "user".to_string() => ArgumentValue::Text("alice".to_string()),
+        );
+
+        let line = fill_placeholder_masking("Log in as {user} with {token}?", &values, &["token".to_string()]);
+
+        assert_eq!(line, Ok("Log in as alice with ****?".to_string()));
+    }
 }
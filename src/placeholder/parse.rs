@@ -1,4 +1,4 @@
-use std::{collections::HashMap, convert::TryInto};
+use std::collections::HashMap;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -7,6 +7,9 @@ use crate::helper::range_shift::RangeShift;
 
 use super::{Placeholder, PlaceholderParseError};
 
+/// The properties given without the value, like `{arg|hex}`. They are treated as having an empty value.
+const VALUELESS_PROPERTIES: [&str; 2] = ["hex", "key"];
+
 static PLACEHOLDER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(^|[^\\]|\\\\)\{(.+?)}").unwrap());
 
 pub(super) fn parse_first_placeholder(line: &str, start: usize) -> Result<Option<Placeholder>, PlaceholderParseError> {
@@ -22,10 +25,14 @@ pub(super) fn parse_first_placeholder(line: &str, start: usize) -> Result<Option
     let arg_name = mat_args.next().unwrap();
     let args = mat_args
         .map(|m| {
-            let [name, value]: [&str; 2] = m.splitn(2, '=')
-                .collect::<Vec<_>>()
-                .try_into()
-                .map_err(|_| PlaceholderParseError::MalformedParameter)?;
+            let (name, value) = match m.split_once('=') {
+                Some(pair) => pair,
+                None if VALUELESS_PROPERTIES.contains(&m) => (m, ""),
+                None => return Err(PlaceholderParseError::MalformedParameter)
+            };
+            if name.is_empty() {
+                return Err(PlaceholderParseError::MalformedParameter);
+            }
 
             Ok((name.to_string(), value.to_string()))
         })
@@ -60,6 +67,7 @@ mod tests {
                 "another" => "hoge=fuga"
             )
         ),
+        case("{args|hex|pad=4}", "args", map!(<&str, &str>; "hex" => "", "pad" => "4")),
     )]
     fn parses_unmalformed_placeholder(placeholder: &str, arg_name: &str, args: HashMap<&str, &str>) {
         let parsed = parse_first_placeholder(placeholder, 0)
@@ -80,6 +88,8 @@ mod tests {
     #[rstest(placeholder,
         case("{args|}"),
         case("{args|namevalue}"),
+        case("{args|=value}"),
+        case("{flag|true}"),
     )]
     fn declines_malformed_placeholder(placeholder: &str) {
         let error = parse_first_placeholder(placeholder, 0)
@@ -1,42 +1,280 @@
 mod tmpfile;
 mod run;
+pub mod schedule;
+pub mod signal;
+mod terminal;
 
-use std::{io::Error, fmt::Display};
+use std::{collections::HashMap, io::Error, fmt::Display, path::Path};
+
+use crate::prompt::warn;
 
 use self::{run::run_command, tmpfile::create_script_file};
 
 pub enum ExecuteStatus {
     Exited(i32),
-    Terminated
+    Terminated(i32)
+}
+impl ExecuteStatus {
+    /// The exit status in the shell's convention, which is `128 + (signal number)` for the terminated one.
+    pub fn code(&self) -> i32 {
+        match self {
+            ExecuteStatus::Exited(c) => *c,
+            ExecuteStatus::Terminated(s) => 128 + s
+        }
+    }
 }
 
 pub enum ExecuteError {
     UnknownEnvironment,
+    MalformedShell(String),
     PreparationFailure(Error)
 }
 impl Display for ExecuteError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ExecuteError::UnknownEnvironment => write!(f, "The location to create temporal file could not be determined."),
+            ExecuteError::MalformedShell(s) => write!(f, "'{}' could not be used as the shell.", s),
             ExecuteError::PreparationFailure(e) => write!(f, "Unexpected error occured during the preparation: {}", e)
         }
     }
 }
 
+#[derive(Clone)]
+pub struct Script {
+    pub name: String,
+    pub body: String,
+    pub shell: Option<String>,
+    pub cwd: Option<String>,
+    pub env: HashMap<String, String>,
+    pub continue_on_error: bool,
+}
+
+pub struct StepFailure {
+    pub step: String,
+    pub cause: Result<ExecuteStatus, ExecuteError>,
+}
+
+/// Runs the scripts in order, and stops at the first failed one unless it allows continuing.
+/// `finally` is always run after that, with the exit status in `RXE_EXIT_STATUS`.
+pub fn run_steps(steps: &[Script], finally: &[Script], prefix: Option<&str>) -> Result<(), StepFailure> {
+    let result = run_each(steps, prefix, true);
+    if finally.is_empty() {
+        return result;
+    }
+
+    let status = match &result {
+        Ok(()) => 0,
+        Err(StepFailure { cause: Ok(s), .. }) => s.code(),
+        Err(_) => 1
+    };
+    let finally: Vec<_> = finally.iter()
+        .cloned()
+        .map(|mut s| {
+            s.env.insert("RXE_EXIT_STATUS".to_string(), status.to_string());
+            s
+        })
+        .collect();
+
+    let finally_result = run_each(&finally, prefix, false);
+    result.and(finally_result)
+}
+
+fn run_each(steps: &[Script], prefix: Option<&str>, interruptible: bool) -> Result<(), StepFailure> {
+    for step in steps {
+        if let Some(signo) = signal::received().filter(|_| interruptible) {
+            return Err(StepFailure { step: step.name.clone(), cause: Ok(ExecuteStatus::Terminated(signo)) });
+        }
+
+        let result = run_script(step, prefix);
+        if matches!(result, Ok(ExecuteStatus::Exited(0))) {
+            continue;
+        }
+
+        if step.continue_on_error {
+            warn(format!("The step '{}' failed, but continuing the execution.", step.name));
+            continue;
+        }
+
+        return Err(StepFailure { step: step.name.clone(), cause: result });
+    }
+
+    Ok(())
+}
+
 #[cfg(target_family = "windows")]
-pub fn run_script(line: &str) -> Result<ExecuteStatus, ExecuteError> {
-    let script_file = create_script_file("ps1", line)?;
-    run_command("powershell", &["-File", &script_file])
+pub fn run_script(script: &Script, prefix: Option<&str>) -> Result<ExecuteStatus, ExecuteError> {
+    if script.body.trim().is_empty() {
+        return Ok(ExecuteStatus::Exited(0));
+    }
+
+    let script_file = create_script_file("ps1", &script.body)?;
+    match &script.shell {
+        None => run_command("powershell", &["-File", &script_file], script, prefix),
+        Some(shell) => run_with_shell(shell, &script_file, script, prefix)
+    }
 }
 
 #[cfg(target_family = "unix")]
-pub fn run_script(line: &str) -> Result<ExecuteStatus, ExecuteError> {
+pub fn run_script(script: &Script, prefix: Option<&str>) -> Result<ExecuteStatus, ExecuteError> {
     use std::{fs, os::unix::prelude::PermissionsExt};
 
-    let script_file = create_script_file("sh", line)?;
+    if script.body.trim().is_empty() {
+        return Ok(ExecuteStatus::Exited(0));
+    }
+
+    let script_file = create_script_file("sh", &script.body)?;
     fs::set_permissions(&script_file, fs::Permissions::from_mode(0o755)).map_err(ExecuteError::PreparationFailure)?;
-    run_command("sh", &["-c", &script_file])
+    match &script.shell {
+        None => run_command("sh", &[&script_file], script, prefix),
+        Some(shell) => run_with_shell(shell, &script_file, script, prefix)
+    }
+}
+
+/// Makes the command which runs the one-line command with the default shell.
+#[cfg(target_family = "windows")]
+pub fn shell_command(command: &str) -> std::process::Command {
+    let mut shell = std::process::Command::new("powershell");
+    shell.args(["-Command", command]);
+
+    shell
+}
+
+/// Makes the command which runs the one-line command with the default shell.
+#[cfg(target_family = "unix")]
+pub fn shell_command(command: &str) -> std::process::Command {
+    let mut shell = std::process::Command::new("sh");
+    shell.args(["-c", command]);
+
+    shell
+}
+
+/// Makes the reference to the environment variable in the script run by the shell, or by the default one if not given.
+/// The reference is quoted unless it is already in the double quotes, so that the value is never split by the shell.
+pub fn env_reference(shell: Option<&str>, variable: &str, in_double_quotes: bool) -> String {
+    let program = shell
+        .and_then(|s| s.split_whitespace().next())
+        .and_then(|p| Path::new(p).file_stem())
+        .map(|p| p.to_string_lossy().to_lowercase());
+    let is_powershell = match program.as_deref() {
+        Some(p) => p == "powershell" || p == "pwsh",
+        None => cfg!(target_family = "windows")
+    };
+
+    match (is_powershell, in_double_quotes) {
+        (true, _) => format!("$env:{}", variable),
+        (false, true) => format!("${{{}}}", variable),
+        (false, false) => format!("\"${{{}}}\"", variable)
+    }
+}
+
+fn run_with_shell(shell: &str, script_file: &str, script: &Script, prefix: Option<&str>) -> Result<ExecuteStatus, ExecuteError> {
+    let mut parts = shell.split_whitespace();
+    let program = parts.next().ok_or_else(|| ExecuteError::MalformedShell(shell.to_string()))?;
+    let args: Vec<&str> = parts.chain([script_file]).collect();
+
+    run_command(program, &args, script, prefix)
 }
 
 #[cfg(not(any(target_family = "windows", target_family = "unix")))]
 compile_error!("This crate cannot be built for the environment which is not either of Windows or Unix family");
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use crate::map;
+    use crate::domain::ArgumentValue;
+    use crate::placeholder::fill_placeholder_hiding;
+    use crate::util::get_random_string;
+
+    use super::{env_reference, run_script, run_steps, ExecuteStatus, Script};
+
+    fn step(name: &str, body: &str, continue_on_error: bool) -> Script {
+        Script {
+            name: name.to_string(),
+            body: body.to_string(),
+            shell: None,
+            cwd: None,
+            env: HashMap::new(),
+            continue_on_error
+        }
+    }
+
+    #[rstest(shell, in_double_quotes, expected,
+        case(None, false, r#""${SECRET}""#),
+        case(None, true, "${SECRET}"),
+        case(Some("bash -e"), false, r#""${SECRET}""#),
+        case(Some("pwsh -File"), false, "$env:SECRET"),
+        case(Some("/usr/local/bin/PowerShell.exe -File"), true, "$env:SECRET"),
+    )]
+    fn refers_environment_variable_for_shell(shell: Option<&str>, in_double_quotes: bool, expected: &str) {
+        assert_eq!(env_reference(shell, "SECRET", in_double_quotes), expected);
+    }
+
+    #[test]
+    fn expands_secret_without_splitting() {
+        let values = map!("token".to_string() => ArgumentValue::Text("a   b*".to_string()));
+        let line = r#"[ {token} = 'a   b*' ] && [ "Bearer {token}" = 'Bearer a   b*' ]"#;
+        let (body, env) = fill_placeholder_hiding(line, &values, &["token".to_string()], |v, q| env_reference(None, v, q)).unwrap();
+
+        // The glob would match the entries in the root directory if the value was not quoted.
+        let script = Script { env, cwd: Some("/".to_string()), ..step("check", &body, false) };
+
+        assert!(matches!(run_script(&script, None), Ok(ExecuteStatus::Exited(0))));
+    }
+
+    #[test]
+    fn names_the_failed_step() {
+        let steps = vec![
+            step("first", "exit 0", false),
+            step("allowed", "exit 2", true),
+            step("failing", "exit 3", false),
+            step("never", "exit 4", false),
+        ];
+
+        let failure = run_steps(&steps, &[], None).err().unwrap();
+        assert_eq!(failure.step, "failing");
+        assert!(matches!(failure.cause, Ok(ExecuteStatus::Exited(3))));
+    }
+
+    #[test]
+    fn passes_cwd_and_env_to_the_step() {
+        let mut script = step("check", r#"[ "$(pwd)" = "/" ] && [ "$RXE_TEST" = "value" ]"#, false);
+        script.cwd = Some("/".to_string());
+        script.env.insert("RXE_TEST".to_string(), "value".to_string());
+
+        assert!(run_steps(&[script], &[], None).is_ok());
+    }
+
+    #[test]
+    fn runs_finally_with_the_exit_status() {
+        let marker = std::env::temp_dir().join(format!("{}.status", get_random_string()));
+        let marker = marker.to_str().unwrap();
+
+        let steps = vec![step("failing", "exit 3", false), step("never", "exit 0", false)];
+        let finally = vec![step("cleanup", &format!(r#"echo "$RXE_EXIT_STATUS" > {}"#, marker), false)];
+
+        let failure = run_steps(&steps, &finally, None).err().unwrap();
+        assert_eq!(failure.step, "failing");
+        assert_eq!(std::fs::read_to_string(marker).unwrap(), "3\n");
+
+        std::fs::remove_file(marker).unwrap();
+    }
+
+    #[test]
+    fn reports_termination_by_signal() {
+        let status = run_script(&step("killed", "kill -TERM $$; sleep 5", false), None);
+
+        assert!(matches!(status, Ok(ExecuteStatus::Terminated(15))));
+        assert_eq!(status.ok().map(|s| s.code()), Some(143));
+    }
+
+    #[test]
+    fn gives_no_input_to_prefixed_script() {
+        let status = run_script(&step("reading", "if read -r line; then exit 1; fi", false), Some("job"));
+
+        assert!(matches!(status, Ok(ExecuteStatus::Exited(0))));
+    }
+}
@@ -1,18 +1,120 @@
-use std::process::Command;
-
-use super::{ExecuteStatus, ExecuteError};
-
-pub fn run_command(program: &str, args: &[&str]) -> Result<ExecuteStatus, ExecuteError> {
-    let code = Command::new(program)
-        .args(args)
-        .spawn()
-        .map_err(ExecuteError::PreparationFailure)?
-        .wait()
-        .map_err(ExecuteError::PreparationFailure)?
-        .code();
-
-    match code {
-        Some(c) => Ok(ExecuteStatus::Exited(c)),
-        None => Ok(ExecuteStatus::Terminated)
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+
+use crate::prompt::{warn, with_color};
+
+use super::{signal::{self, GroupGuard}, terminal, ExecuteStatus, ExecuteError, Script};
+
+pub fn run_command(program: &str, args: &[&str], script: &Script, prefix: Option<&str>) -> Result<ExecuteStatus, ExecuteError> {
+    let mut command = Command::new(program);
+    command.args(args).envs(&script.env);
+    if let Some(cwd) = &script.cwd {
+        command.current_dir(cwd);
+    }
+
+    let foreground = prefix.is_none() && terminal::is_foreground();
+    isolate(&mut command, foreground);
+
+    let status = match prefix {
+        None => {
+            let mut child = command
+                .spawn()
+                .map_err(ExecuteError::PreparationFailure)?;
+
+            let _group = register(&child, script);
+            let _terminal = foreground.then(|| terminal::hand_over(child.id()));
+
+            child.wait().map_err(ExecuteError::PreparationFailure)?
+        },
+        Some(prefix) => {
+            // The job in the background would be stopped by SIGTTIN if it read the terminal.
+            let mut child = command
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(ExecuteError::PreparationFailure)?;
+
+            let _group = register(&child, script);
+
+            let prefix = with_color(6, format!("[{}] ", prefix));
+            let stdout = child.stdout.take();
+            let stderr = child.stderr.take();
+
+            thread::scope(|s| {
+                s.spawn(|| stdout.map(|o| forward_lines(o, io::stdout(), &prefix)));
+                s.spawn(|| stderr.map(|e| forward_lines(e, io::stderr(), &prefix)));
+
+                child.wait()
+            })
+            .map_err(ExecuteError::PreparationFailure)?
+        }
+    };
+
+    Ok(to_execute_status(status))
+}
+
+fn register(child: &Child, script: &Script) -> Option<GroupGuard> {
+    let group = signal::register(child.id());
+    if group.is_none() {
+        warn(format!("Too many scripts are running, so the signals will not be forwarded to '{}'.", script.name));
+    }
+
+    group
+}
+
+/// Puts the script into its own process group, so that the signal can be forwarded to
+/// all of the processes started by the script.
+#[cfg(target_family = "unix")]
+fn isolate(command: &mut Command, foreground: bool) {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+    if foreground {
+        // SAFETY: Only async-signal-safe functions are called in the closure.
+        unsafe {
+            command.pre_exec(|| {
+                terminal::set_foreground(libc::getpgrp());
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(target_family = "windows")]
+fn isolate(_command: &mut Command, _foreground: bool) {}
+
+#[cfg(target_family = "unix")]
+fn to_execute_status(status: ExitStatus) -> ExecuteStatus {
+    use std::os::unix::process::ExitStatusExt;
+
+    match status.code() {
+        Some(c) => ExecuteStatus::Exited(c),
+        None => ExecuteStatus::Terminated(status.signal().unwrap_or_default())
+    }
+}
+
+#[cfg(target_family = "windows")]
+fn to_execute_status(status: ExitStatus) -> ExecuteStatus {
+    ExecuteStatus::Exited(status.code().unwrap_or(1))
+}
+
+fn forward_lines(from: impl Read, mut to: impl Write, prefix: &str) {
+    let mut reader = BufReader::new(from);
+    let mut line = Vec::new();
+
+    while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+        if !line.ends_with(b"\n") {
+            line.push(b'\n');
+        }
+
+        // Writing the whole line at once so that the lines from the other tasks are not mixed in.
+        let buffer = [prefix.as_bytes(), &line].concat();
+        if to.write_all(&buffer).is_err() {
+            return;
+        }
+
+        line.clear();
     }
 }
@@ -0,0 +1,146 @@
+use std::sync::mpsc;
+use std::thread;
+
+use super::{run_steps, signal, Script, StepFailure};
+
+pub struct Job {
+    pub name: String,
+    pub steps: Vec<Script>,
+    pub finally: Vec<Script>,
+    pub needs: Vec<usize>,
+}
+
+pub enum JobResult {
+    Done(Result<(), StepFailure>),
+    Skipped
+}
+impl JobResult {
+    pub fn is_success(&self) -> bool {
+        matches!(self, JobResult::Done(Ok(())))
+    }
+}
+
+/// Runs the jobs, starting each job after all of the jobs in its `needs` succeeded.
+/// `needs` of the job should only point the jobs placed before it.
+pub fn run_jobs(jobs: &[Job], parallelism: usize, keep_going: bool) -> Vec<JobResult> {
+    let mut results: Vec<Option<JobResult>> = jobs.iter().map(|_| None).collect();
+    let mut started = vec![false; jobs.len()];
+    let prefixed = parallelism > 1;
+
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|s| {
+        let mut running = 0;
+        let mut failed = false;
+
+        loop {
+            for (i, job) in jobs.iter().enumerate() {
+                let need_failed = job.needs.iter().any(|n| matches!(&results[*n], Some(r) if !r.is_success()));
+                if !started[i] && need_failed {
+                    started[i] = true;
+                    results[i] = Some(JobResult::Skipped);
+                }
+            }
+
+            if (keep_going || !failed) && signal::received().is_none() {
+                for (i, job) in jobs.iter().enumerate() {
+                    if running >= parallelism {
+                        break;
+                    }
+
+                    let ready = job.needs.iter().all(|n| matches!(&results[*n], Some(r) if r.is_success()));
+                    if started[i] || !ready {
+                        continue;
+                    }
+
+                    started[i] = true;
+                    running += 1;
+
+                    let tx = tx.clone();
+                    s.spawn(move || {
+                        let prefix = prefixed.then_some(job.name.as_str());
+                        tx.send((i, run_steps(&job.steps, &job.finally, prefix))).ok();
+                    });
+                }
+            }
+
+            if running == 0 {
+                break;
+            }
+
+            // SAFETY: The sender is held in this scope, and running jobs always send their result.
+            let (i, result) = rx.recv().unwrap();
+            running -= 1;
+
+            let result = JobResult::Done(result);
+            failed |= !result.is_success();
+            results[i] = Some(result);
+        }
+    });
+
+    results.into_iter().map(|r| r.unwrap_or(JobResult::Skipped)).collect()
+}
+
+#[cfg(all(test, target_family = "unix"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use crate::runner::Script;
+    use crate::util::get_random_string;
+
+    use super::{run_jobs, Job, JobResult};
+
+    fn job(name: &str, script: &str, needs: Vec<usize>) -> Job {
+        let step = Script {
+            name: name.to_string(),
+            body: script.to_string(),
+            shell: None,
+            cwd: None,
+            env: HashMap::new(),
+            continue_on_error: false
+        };
+
+        Job { name: name.to_string(), steps: vec![step], finally: vec![], needs }
+    }
+
+    #[rstest(keep_going, expected,
+        case(false, vec![true, true, false, false]),
+        case(true, vec![true, true, false, true]),
+    )]
+    fn skips_jobs_after_failure(keep_going: bool, expected: Vec<bool>) {
+        let jobs = vec![
+            job("a", "exit 0", vec![]),
+            job("b", "exit 1", vec![0]),
+            job("c", "exit 0", vec![1]),
+            job("d", "exit 0", vec![0]),
+        ];
+
+        let results = run_jobs(&jobs, 1, keep_going);
+        let ran: Vec<_> = results.iter().map(|r| !matches!(r, JobResult::Skipped)).collect();
+        assert_eq!(ran, expected);
+    }
+
+    #[test]
+    fn runs_every_independent_job_in_parallel() {
+        // Each of a and b waits for the other to start, so that they succeed only if they overlap.
+        let marker = std::env::temp_dir().join(get_random_string());
+        let marker = marker.to_str().unwrap();
+        let wait_for = |own: &str, other: &str| format!(
+            "touch {m}.{own}; for i in $(seq 50); do [ -e {m}.{other} ] && exit 0; sleep 0.1; done; exit 1",
+            m = marker, own = own, other = other
+        );
+
+        let jobs = vec![
+            job("a", &wait_for("a", "b"), vec![]),
+            job("b", &wait_for("b", "a"), vec![]),
+            job("c", "exit 0", vec![0, 1]),
+        ];
+
+        let results = run_jobs(&jobs, 2, false);
+        let _ = std::fs::remove_file(format!("{}.a", marker));
+        let _ = std::fs::remove_file(format!("{}.b", marker));
+
+        assert!(results.iter().all(JobResult::is_success));
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+const GROUP_SLOTS: usize = 64;
+
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+static GROUPS: [AtomicI32; GROUP_SLOTS] = [const { AtomicI32::new(0) }; GROUP_SLOTS];
+
+/// Keeps rxe alive on the interruption so that the cleanup can be done after the script stopped,
+/// and forwards the signal to the process groups of the running scripts.
+#[cfg(target_family = "unix")]
+pub fn trap() {
+    extern "C" fn handle(signo: libc::c_int) {
+        RECEIVED.store(signo, Ordering::SeqCst);
+        forward(signo);
+    }
+
+    for signo in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        // SAFETY: The handler only touches the atomic values and kill(2), which are async-signal-safe.
+        unsafe {
+            libc::signal(signo, handle as extern "C" fn(libc::c_int) as libc::sighandler_t);
+        }
+    }
+}
+
+#[cfg(target_family = "windows")]
+pub fn trap() {}
+
+pub fn received() -> Option<i32> {
+    match RECEIVED.load(Ordering::SeqCst) {
+        0 => None,
+        s => Some(s)
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn forward(signo: i32) {
+    for group in &GROUPS {
+        let group = group.load(Ordering::SeqCst);
+        if group != 0 {
+            // SAFETY: kill(2) does not touch any memory.
+            unsafe {
+                libc::kill(-group, signo);
+            }
+        }
+    }
+}
+
+#[cfg(target_family = "windows")]
+fn forward(_signo: i32) {}
+
+/// Registration of the process group which the received signals are forwarded to.
+/// The registration is removed when this is dropped.
+pub struct GroupGuard(usize);
+impl Drop for GroupGuard {
+    fn drop(&mut self) {
+        GROUPS[self.0].store(0, Ordering::SeqCst);
+    }
+}
+
+/// Registers the process group. `None` is returned if too many groups are already registered,
+/// in which case the signals are not forwarded to the group.
+pub fn register(group: u32) -> Option<GroupGuard> {
+    let slot = GROUPS.iter().position(|g| {
+        g.compare_exchange(0, group as i32, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+    })?;
+
+    Some(GroupGuard(slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{register, GROUP_SLOTS};
+
+    #[test]
+    fn declines_registration_when_slots_are_full() {
+        let guards: Vec<_> = (0..=GROUP_SLOTS).map_while(|_| register(i32::MAX as u32)).collect();
+
+        assert!(guards.len() <= GROUP_SLOTS);
+        drop(guards);
+    }
+}
@@ -0,0 +1,55 @@
+/// Whether rxe is in the foreground of the terminal, in which case the script should take it over.
+#[cfg(target_family = "unix")]
+pub fn is_foreground() -> bool {
+    // SAFETY: These functions only query the state of the process.
+    unsafe {
+        libc::isatty(libc::STDIN_FILENO) == 1 && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+    }
+}
+
+#[cfg(target_family = "windows")]
+pub fn is_foreground() -> bool {
+    false
+}
+
+/// Makes the process group the foreground of the terminal.
+/// Only async-signal-safe functions are used, so this can be called between fork and exec.
+#[cfg(target_family = "unix")]
+pub fn set_foreground(group: i32) {
+    // SAFETY: The signal mask is restored before returning.
+    unsafe {
+        let mut mask: libc::sigset_t = std::mem::zeroed();
+        let mut original: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut mask);
+        libc::sigaddset(&mut mask, libc::SIGTTOU);
+
+        // SIGTTOU is sent if this is called from the background, so it is blocked during the call.
+        libc::pthread_sigmask(libc::SIG_BLOCK, &mask, &mut original);
+        libc::tcsetpgrp(libc::STDIN_FILENO, group);
+        libc::pthread_sigmask(libc::SIG_SETMASK, &original, std::ptr::null_mut());
+    }
+}
+
+/// Gives the terminal back to rxe when this is dropped.
+pub struct ForegroundGuard;
+impl Drop for ForegroundGuard {
+    #[cfg(target_family = "unix")]
+    fn drop(&mut self) {
+        // SAFETY: getpgrp(2) always succeeds.
+        set_foreground(unsafe { libc::getpgrp() });
+    }
+
+    #[cfg(target_family = "windows")]
+    fn drop(&mut self) {}
+}
+
+#[cfg(target_family = "unix")]
+pub fn hand_over(group: u32) -> ForegroundGuard {
+    set_foreground(group as i32);
+    ForegroundGuard
+}
+
+#[cfg(target_family = "windows")]
+pub fn hand_over(_group: u32) -> ForegroundGuard {
+    ForegroundGuard
+}